@@ -5,7 +5,7 @@ use clap::Parser;
 use color_eyre::Result;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
-use interactsh_rs::prelude::{ParsedLogEntry, RawLog};
+use interactsh_rs::prelude::{LogEntry, LogProtocol, ParsedLogEntry, RawLog};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 #[cfg(feature = "tokio")]
@@ -37,6 +37,10 @@ pub struct ClientCli {
     /// Output raw logs instead of parsed logs
     #[clap(short = 'r', long = "raw-logs")]
     pub raw_logs: bool,
+
+    /// Only output logs of this protocol (dns, http, ftp, smtp, ldap, smb, or raw)
+    #[clap(short = 'p', long)]
+    pub protocol: Option<LogProtocol>,
 }
 
 pub fn start_spinner(msg: String) -> ProgressBar {
@@ -92,10 +96,32 @@ pub fn print_interaction_url(interaction_fqdn: String) {
     );
 }
 
+/// Checks if the log entry matches the protocol filter, if one was set
+pub fn matches_protocol(log_entry: &LogEntry, protocol_filter: Option<&LogProtocol>) -> bool {
+    let protocol_filter = match protocol_filter {
+        Some(protocol) => protocol,
+        None => return true,
+    };
+
+    match log_entry {
+        LogEntry::ParsedLog(log) => log.log_protocol() == *protocol_filter,
+        LogEntry::RawLog(_) => protocol_filter.as_tag().eq_ignore_ascii_case("raw"),
+    }
+}
+
 pub trait LogDisplay {
     fn as_formatted_log_string(&self) -> String;
 }
 
+impl LogDisplay for LogEntry {
+    fn as_formatted_log_string(&self) -> String {
+        match self {
+            LogEntry::ParsedLog(log) => log.as_formatted_log_string(),
+            LogEntry::RawLog(log) => log.as_formatted_log_string(),
+        }
+    }
+}
+
 impl LogDisplay for RawLog {
     fn as_formatted_log_string(&self) -> String {
         format!(
@@ -108,6 +134,8 @@ impl LogDisplay for RawLog {
 
 impl LogDisplay for ParsedLogEntry {
     fn as_formatted_log_string(&self) -> String {
+        let log_type = print_protocol_log_type(&self.log_protocol());
+
         match self {
             ParsedLogEntry::Dns {
                 unique_id: _,
@@ -122,7 +150,7 @@ impl LogDisplay for ParsedLogEntry {
                     Some(dns_q_type) => {
                         format!(
                             "{log_type}\n{id}\n{q_type}\n{raw_req}\n{raw_res}\n{addr}\n{timestamp}\n",
-                            log_type = print_log_type("DNS Log"),
+                            log_type = log_type,
                             id = print_normal_param("ID", full_id.as_str()),
                             q_type = print_normal_param("Q Type", dns_q_type.to_string().as_str()),
                             raw_req = print_raw_param("Raw Request", raw_request.as_str()),
@@ -134,7 +162,7 @@ impl LogDisplay for ParsedLogEntry {
                     None => {
                         format!(
                             "{log_type}\n{id}\n{raw_req}\n{raw_res}\n{addr}\n{timestamp}\n",
-                            log_type = print_log_type("DNS Log"),
+                            log_type = log_type,
                             id = print_normal_param("ID", full_id.as_str()),
                             raw_req = print_raw_param("Raw Request", raw_request.as_str()),
                             raw_res = print_raw_param("Raw Response", raw_response.as_str()),
@@ -154,7 +182,7 @@ impl LogDisplay for ParsedLogEntry {
             } => {
                 format!(
                     "{log_type}\n{addr}\n{raw_req}\n{timestamp}\n",
-                    log_type = print_log_type("FTP Log"),
+                    log_type = log_type,
                     addr =
                         print_normal_param("Remote Address", remote_address.to_string().as_str()),
                     raw_req = print_raw_param("Raw Request", raw_request.as_str()),
@@ -171,7 +199,7 @@ impl LogDisplay for ParsedLogEntry {
             } => {
                 format!(
                     "{log_type}\n{id}\n{raw_req}\n{raw_res}\n{addr}\n{timestamp}\n",
-                    log_type = print_log_type("HTTP Log"),
+                    log_type = log_type,
                     id = print_normal_param("ID", full_id.as_str()),
                     raw_req = print_raw_param("Raw Request", raw_request.as_str()),
                     raw_res = print_raw_param("Raw Response", raw_response.as_str()),
@@ -190,7 +218,7 @@ impl LogDisplay for ParsedLogEntry {
             } => {
                 format!(
                     "{log_type}\n{id}\n{raw_req}\n{raw_res}\n{addr}\n{timestamp}\n",
-                    log_type = print_log_type("LDAP Log"),
+                    log_type = log_type,
                     id = print_normal_param("ID", full_id.as_str()),
                     raw_req = print_raw_param("Raw Request", raw_request.as_str()),
                     raw_res = print_raw_param("Raw Response", raw_response.as_str()),
@@ -199,13 +227,27 @@ impl LogDisplay for ParsedLogEntry {
                     timestamp = print_timestamp(timestamp),
                 )
             }
+            ParsedLogEntry::Responder {
+                raw_request,
+                remote_address,
+                timestamp,
+            } => {
+                format!(
+                    "{log_type}\n{addr}\n{raw_req}\n{timestamp}\n",
+                    log_type = log_type,
+                    addr =
+                        print_normal_param("Remote Address", remote_address.to_string().as_str()),
+                    raw_req = print_raw_param("Raw Request", raw_request.as_str()),
+                    timestamp = print_timestamp(timestamp),
+                )
+            }
             ParsedLogEntry::Smb {
                 raw_request,
                 timestamp,
             } => {
                 format!(
                     "{log_type}\n{raw_req}\n{timestamp}\n",
-                    log_type = print_log_type("SMB Log"),
+                    log_type = log_type,
                     raw_req = print_raw_param("Raw Request", raw_request.as_str()),
                     timestamp = print_timestamp(timestamp),
                 )
@@ -220,7 +262,7 @@ impl LogDisplay for ParsedLogEntry {
             } => {
                 format!(
                     "{log_type}\n{id}\n{raw_req}\n{from}\n{addr}\n{timestamp}\n",
-                    log_type = print_log_type("SMTP Log"),
+                    log_type = log_type,
                     id = print_normal_param("ID", full_id.as_str()),
                     raw_req = print_raw_param("Raw Request", raw_request.as_str()),
                     from = print_normal_param("SMTP From", smtp_from.as_str()),
@@ -229,11 +271,24 @@ impl LogDisplay for ParsedLogEntry {
                     timestamp = print_timestamp(timestamp),
                 )
             }
+            ParsedLogEntry::Other { fields, .. } => {
+                format!(
+                    "{log_type}\n{fields}\n",
+                    log_type = log_type,
+                    fields = print_raw_param("Fields", fields.to_string().as_str()),
+                )
+            }
         }
     }
 }
 
 // ParsedLog display helpers
+fn print_protocol_log_type(log_protocol: &LogProtocol) -> String {
+    let log_type = format!("{} Log", log_protocol.as_tag().to_ascii_uppercase());
+
+    print_log_type(&log_type)
+}
+
 fn print_log_type(log_type: &str) -> String {
     format!(
         "{}{}",
@@ -272,3 +327,59 @@ fn print_timestamp(timestamp: &OffsetDateTime) -> String {
 
     print_normal_param_nocolor("timestamp", formatted_timestamp)
 }
+
+#[cfg(test)]
+mod tests {
+    use interactsh_rs::interaction_log::parse_log_entry;
+
+    use super::*;
+
+    fn polled_logs() -> Vec<LogEntry> {
+        let dns_log = r#"{"protocol": "dns", "unique-id": "abc", "full-id": "abc", "raw-request": "req", "raw-response": "res", "remote-address": "127.0.0.1", "timestamp": "2022-10-12T20:28:11.123456789Z"}"#;
+        let http_log = r#"{"protocol": "http", "unique-id": "abc", "full-id": "abc", "raw-request": "req", "raw-response": "res", "remote-address": "127.0.0.1", "timestamp": "2022-10-12T20:28:11.123456789Z"}"#;
+
+        vec![
+            parse_log_entry(dns_log, true),
+            parse_log_entry(http_log, true),
+            parse_log_entry("not a json log", true),
+        ]
+    }
+
+    fn filtered_logs<'a>(logs: &'a [LogEntry], args: &[&str]) -> Vec<&'a LogEntry> {
+        let cli = ClientCli::parse_from(args);
+
+        logs.iter()
+            .filter(|log_entry| matches_protocol(log_entry, cli.protocol.as_ref()))
+            .collect()
+    }
+
+    #[test]
+    fn protocol_filter_only_keeps_matching_logs() {
+        let logs = polled_logs();
+
+        let http_logs = filtered_logs(&logs, &["cli", "--protocol", "http"]);
+
+        assert_eq!(http_logs.len(), 1);
+        assert!(matches!(
+            http_logs[0],
+            LogEntry::ParsedLog(ParsedLogEntry::Http { .. })
+        ));
+    }
+
+    #[test]
+    fn raw_protocol_filter_keeps_raw_logs() {
+        let logs = polled_logs();
+
+        let raw_logs = filtered_logs(&logs, &["cli", "-p", "raw"]);
+
+        assert_eq!(raw_logs.len(), 1);
+        assert!(matches!(raw_logs[0], LogEntry::RawLog(_)));
+    }
+
+    #[test]
+    fn no_protocol_filter_keeps_all_logs() {
+        let logs = polled_logs();
+
+        assert_eq!(filtered_logs(&logs, &["cli"]).len(), logs.len());
+    }
+}
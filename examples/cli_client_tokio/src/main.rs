@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use clap::Parser;
 use cli_client_shared::{
+    matches_protocol,
     print_interaction_url,
     start_ctrlc_listener,
     start_spinner,
@@ -25,7 +26,9 @@ async fn main() {
 
 async fn run_cli_client() -> Result<()> {
     // Build the client
-    let client = build_client()?;
+    let cli = ClientCli::parse();
+    let protocol_filter = cli.protocol.clone();
+    let client = build_client(cli)?;
 
     // Register the client
     let spinner = start_spinner("Registering the client...".to_owned());
@@ -38,7 +41,7 @@ async fn run_cli_client() -> Result<()> {
     // Poll server
     tokio::time::sleep(Duration::from_secs(5)).await;
     println!("Polling the server...");
-    poll_server(&client, shutdown_rx).await?;
+    poll_server(&client, shutdown_rx, protocol_filter.as_ref()).await?;
 
     // Deregister the client
     let spinner = start_spinner("Deregistering the client...".to_owned());
@@ -48,8 +51,8 @@ async fn run_cli_client() -> Result<()> {
     Ok(())
 }
 
-fn build_client() -> Result<UnregisteredClient> {
-    let builder = get_builder_from_cli();
+fn build_client(cli: ClientCli) -> Result<UnregisteredClient> {
+    let builder = get_builder_from_cli(cli);
 
     let spinner = start_spinner("Building the client...".to_owned());
     let client = builder.build()?;
@@ -58,8 +61,7 @@ fn build_client() -> Result<UnregisteredClient> {
     Ok(client)
 }
 
-fn get_builder_from_cli() -> ClientBuilder {
-    let cli = ClientCli::parse();
+fn get_builder_from_cli(cli: ClientCli) -> ClientBuilder {
     let mut builder = ClientBuilder::default();
 
     builder = if let Some(server) = cli.server {
@@ -92,25 +94,30 @@ fn get_builder_from_cli() -> ClientBuilder {
     builder
 }
 
-async fn poll_server(client: &RegisteredClient, shutdown_rx: oneshot::Receiver<()>) -> Result<()> {
+async fn poll_server(
+    client: &RegisteredClient,
+    shutdown_rx: oneshot::Receiver<()>,
+    protocol_filter: Option<&LogProtocol>,
+) -> Result<()> {
     tokio::select! {
         _ = shutdown_rx => return Ok(()),
-        result = poll_loop(client) => return result,
+        result = poll_loop(client, protocol_filter) => return result,
     }
 }
 
-async fn poll_loop(client: &RegisteredClient) -> Result<()> {
+async fn poll_loop(client: &RegisteredClient, protocol_filter: Option<&LogProtocol>) -> Result<()> {
     loop {
         let logs = match client.poll().await? {
             Some(logs) => logs,
             None => continue,
         };
 
-        for log_entry in logs.iter() {
-            match log_entry {
-                LogEntry::ParsedLog(log) => println!("{}", log.as_formatted_log_string()),
-                LogEntry::RawLog(log) => println!("{}", log.as_formatted_log_string()),
-            }
+        let filtered_logs = logs
+            .iter()
+            .filter(|log_entry| matches_protocol(log_entry, protocol_filter));
+
+        for log_entry in filtered_logs {
+            println!("{}", log_entry.as_formatted_log_string());
         }
 
         tokio::time::sleep(Duration::from_secs(5)).await;
@@ -0,0 +1,194 @@
+//! Blocking versions of the clients, for use in synchronous code.
+//!
+//! The clients in this module wrap the async clients and run each request to
+//! completion on a small internal runtime, similar to reqwest's blocking
+//! client. Their functions mirror the async ones and return the same errors.
+//!
+//! The blocking clients must not be used from within an async context (for
+//! example, inside a function run by tokio), since starting the internal
+//! runtime from there panics.
+
+use snafu::ResultExt;
+use tokio::runtime::{Builder, Runtime};
+
+use super::errors::{
+    client_build_error,
+    ClientBuildError,
+    ClientPollError,
+    ClientRegistrationError,
+    RegistrationError,
+};
+use crate::interaction_log::LogEntry;
+
+
+/// Blocking version of an [UnregisteredClient](crate::client::UnregisteredClient)
+///
+/// See [build_blocking](crate::client::ClientBuilder::build_blocking()).
+pub struct UnregisteredClient {
+    client: super::UnregisteredClient,
+    runtime: Runtime,
+}
+
+impl UnregisteredClient {
+    /// Wraps an async [UnregisteredClient](crate::client::UnregisteredClient),
+    /// such as the one returned in a failed registration's error, in a new
+    /// blocking client.
+    pub fn new(client: super::UnregisteredClient) -> Result<Self, ClientBuildError> {
+        Ok(Self {
+            client,
+            runtime: build_runtime()?,
+        })
+    }
+
+    /// Gets the interaction FQDN that the client is expected to have once
+    /// registered.
+    ///
+    /// See [predicted_fqdn](crate::client::UnregisteredClient::predicted_fqdn()).
+    pub fn predicted_fqdn(&self) -> Option<String> {
+        self.client.predicted_fqdn()
+    }
+
+    /// Registers the client with the Interactsh server, blocking until the
+    /// registration finishes.
+    ///
+    /// See [register](crate::client::UnregisteredClient::register()).
+    // The error hands back the client, the same as the async API's error, so
+    // it is not boxed here either
+    #[allow(clippy::result_large_err)]
+    pub fn register(
+        self,
+    ) -> Result<RegisteredClient, ClientRegistrationError<super::UnregisteredClient>> {
+        let client = self.runtime.block_on(self.client.register())?;
+
+        Ok(RegisteredClient {
+            client,
+            runtime: self.runtime,
+        })
+    }
+}
+
+/// Blocking version of a [RegisteredClient](crate::client::RegisteredClient)
+pub struct RegisteredClient {
+    client: super::RegisteredClient,
+    runtime: Runtime,
+}
+
+impl RegisteredClient {
+    /// Wraps an async [RegisteredClient](crate::client::RegisteredClient),
+    /// such as the one returned in a failed deregistration's error, in a new
+    /// blocking client.
+    pub fn new(client: super::RegisteredClient) -> Result<Self, ClientBuildError> {
+        Ok(Self {
+            client,
+            runtime: build_runtime()?,
+        })
+    }
+
+    /// Gets the interaction FQDN for the current registered session
+    pub fn get_interaction_fqdn(&self) -> String {
+        self.client.get_interaction_fqdn()
+    }
+
+    /// Gets the correlation ID for the current registered session
+    pub fn get_correlation_id(&self) -> &str {
+        self.client.get_correlation_id()
+    }
+
+    /// Polls the Interactsh server for any new logs, blocking until the poll
+    /// finishes.
+    ///
+    /// See [poll](crate::client::RegisteredClient::poll()).
+    pub fn poll(&self) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
+        self.runtime.block_on(self.client.poll())
+    }
+
+    /// Deregisters the client with the Interactsh server, blocking until the
+    /// deregistration finishes.
+    ///
+    /// See [deregister](crate::client::RegisteredClient::deregister()).
+    // The error hands back the client, the same as the async API's error, so
+    // it is not boxed here either
+    #[allow(clippy::result_large_err)]
+    pub fn deregister(self) -> Result<(), ClientRegistrationError<super::RegisteredClient>> {
+        self.runtime.block_on(self.client.deregister())
+    }
+
+    /// Deregisters the client with the Interactsh server without consuming
+    /// it, blocking until the deregistration finishes.
+    ///
+    /// See [try_deregister](crate::client::RegisteredClient::try_deregister()).
+    pub fn try_deregister(&mut self) -> Result<(), RegistrationError> {
+        self.runtime.block_on(self.client.try_deregister())
+    }
+
+    /// Checks whether the client is still registered
+    ///
+    /// See [is_registered](crate::client::RegisteredClient::is_registered()).
+    pub fn is_registered(&self) -> bool {
+        self.client.is_registered()
+    }
+}
+
+/// Builds the single threaded runtime that runs the requests of a blocking
+/// client
+fn build_runtime() -> Result<Runtime, ClientBuildError> {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context(client_build_error::RuntimeBuildFailed)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock_server::{MockResponse, MockServer};
+    use crate::client::ClientBuilder;
+
+    #[test]
+    fn blocking_register_returns_async_client_on_failure() {
+        let client = ClientBuilder::new()
+            .with_server("127.0.0.1".into())
+            .with_server_port(9)
+            .use_tls(false)
+            .with_rsa_key_size(2048)
+            .build_blocking()
+            .expect("Blocking build failed");
+        let predicted_fqdn = client.predicted_fqdn();
+
+        let registration_error = match client.register() {
+            Ok(_) => panic!("Registration with a closed port succeeded"),
+            Err(registration_error) => registration_error,
+        };
+
+        let client = UnregisteredClient::new(registration_error.client)
+            .expect("Failed to rewrap the client");
+        assert_eq!(client.predicted_fqdn(), predicted_fqdn);
+    }
+
+    #[test]
+    fn blocking_try_deregister_marks_the_client_deregistered() {
+        let server = MockServer::start();
+        server.queue_response("/deregister", MockResponse::ok("deregistration successful"));
+        let mut client = RegisteredClient::new(
+            server
+                .client_builder()
+                .build()
+                .expect("Build failed")
+                .into_registered()
+                .expect("Failed to convert the client"),
+        )
+        .expect("Failed to wrap the client");
+        assert!(client.is_registered());
+
+        client
+            .try_deregister()
+            .expect("Deregistration with the mock server failed");
+        client
+            .try_deregister()
+            .expect("Repeated deregistration contacted the server");
+
+        assert!(!client.is_registered());
+        assert_eq!(server.requests_to("/deregister").len(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! Runs blocking work (such as RSA key generation) on a separate thread, so
+//! that it doesn't stall the async executor of whichever runtime is in use.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// Name of the threads that run the blocking work
+const BLOCKING_THREAD_NAME: &str = "interactsh-blocking";
+
+
+/// Runs the function on a new thread, returning its result once it finishes.
+///
+/// If the thread can't be spawned, the function is run on the current
+/// thread instead. If the function panics, the panic is resumed when the
+/// returned future is polled.
+pub(crate) async fn run_blocking<T, F>(blocking_fn: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let shared_state = Arc::new(Mutex::new(TaskState {
+        result: None,
+        waker: None,
+    }));
+    let blocking_fn = Arc::new(Mutex::new(Some(blocking_fn)));
+
+    let thread_state = Arc::clone(&shared_state);
+    let thread_fn = Arc::clone(&blocking_fn);
+    let spawn_result = thread::Builder::new()
+        .name(BLOCKING_THREAD_NAME.into())
+        .spawn(move || {
+            let blocking_fn = thread_fn
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+
+            if let Some(blocking_fn) = blocking_fn {
+                let result = panic::catch_unwind(AssertUnwindSafe(blocking_fn));
+                thread_state
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .complete(result);
+            }
+        });
+
+    if spawn_result.is_err() {
+        let blocking_fn = blocking_fn
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        if let Some(blocking_fn) = blocking_fn {
+            return blocking_fn();
+        }
+    }
+
+    BlockingTask { shared_state }.await
+}
+
+
+/// The result of the blocking work, shared between the thread and the future
+struct TaskState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> TaskState<T> {
+    /// Stores the result and wakes the task waiting on it
+    fn complete(&mut self, result: thread::Result<T>) {
+        self.result = Some(result);
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future that resolves once the blocking work has finished
+struct BlockingTask<T> {
+    shared_state: Arc<Mutex<TaskState<T>>>,
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared_state = self
+            .shared_state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match shared_state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(panic_payload)) => panic::resume_unwind(panic_payload),
+            None => {
+                shared_state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn blocking_fn_result_is_returned() {
+        let result = run_blocking(|| thread::current().name().map(str::to_owned)).await;
+
+        assert_eq!(result.as_deref(), Some(BLOCKING_THREAD_NAME));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "blocking panic")]
+    async fn blocking_fn_panic_is_resumed() {
+        run_blocking(|| panic!("blocking panic")).await
+    }
+}
@@ -1,17 +1,35 @@
 use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 use std::time::Duration;
 
-use rand::distributions::{Alphanumeric, DistString};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use reqwest::Proxy;
-use secrecy::Secret;
-use snafu::{OptionExt, ResultExt};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Certificate, Proxy, RequestBuilder};
+use secrecy::{ExposeSecret, Secret};
+use snafu::{ensure, OptionExt, ResultExt};
 use uuid::Uuid;
 
-use super::errors::{client_build_error, ClientBuildError};
-use super::unregistered::UnregisteredClient;
-use crate::crypto::rsa::RSAPrivKey;
+#[cfg(feature = "blocking")]
+use super::blocking;
+use super::blocking_task;
+#[cfg(feature = "cert-pinning")]
+use super::cert_pinning;
+use super::correlation::{CorrelationConfig, CorrelationData, CorrelationGenerator};
+use super::errors::{client_build_error, quick_client_error, ClientBuildError, QuickClientError};
+use super::http_utils::{
+    self,
+    EndpointPaths,
+    RegisterPayloadHook,
+    RequestCustomizer,
+    UserAgentRotation,
+    DEFAULT_PLAINTEXT_SERVER_PORT,
+    DEFAULT_SERVER_PORT,
+};
+use super::registered::RegisteredClient;
+use super::session::SessionState;
+use super::sink::{LogSink, LogSinks, SinkErrorPolicy};
+use super::unregistered::{RsaKeyState, UnregisteredClient};
+use crate::crypto::rsa::{OaepHash, RSAPrivKey, RsaPadding};
 
 /// The default list of servers provided by the Interactsh team
 const DEFAULT_INTERACTSH_SERVERS: &[&str] = &[
@@ -23,16 +41,115 @@ const DEFAULT_INTERACTSH_SERVERS: &[&str] = &[
     // "oast.me",
 ];
 
+/// The phases of RSA key generation reported to the
+/// [keygen progress](ClientBuilder::with_keygen_progress()) callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyGenPhase {
+    /// Key generation is about to start
+    Started {
+        /// The size of the key being generated, in bits
+        key_size: usize,
+    },
+
+    /// Key generation has finished, whether or not it succeeded
+    Finished,
+}
+
+/// An auth token used to authenticate with the Interactsh server, and how
+/// it is sent in the `Authorization` header
+#[derive(Debug, Clone)]
+pub enum AuthToken {
+    /// The token is sent as-is (`Authorization: <token>`), as expected by
+    /// the Interactsh server
+    SimpleAuth(Secret<String>),
+
+    /// The token is sent with the Bearer scheme (`Authorization: Bearer <token>`)
+    BearerAuth(Secret<String>),
+}
+
+/// TLS backend used by the client when both the `rustls-tls` and
+/// `native-tls` feature flags are enabled
+///
+/// When only one of the feature flags is enabled, its backend is always used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsOption {
+    /// Use [Rustls](https://github.com/rustls/rustls) (the default)
+    #[default]
+    RustlsTls,
+
+    /// Use the OS native TLS library
+    NativeTls,
+}
+
+impl TlsOption {
+    /// Gets the backend that will actually be used with the enabled
+    /// feature flags
+    fn effective_backend(self) -> Self {
+        cfg_if::cfg_if! {
+            if #[cfg(all(feature = "rustls-tls", feature = "native-tls"))] {
+                self
+            } else if #[cfg(feature = "rustls-tls")] {
+                TlsOption::RustlsTls
+            } else {
+                TlsOption::NativeTls
+            }
+        }
+    }
+
+    /// Applies the backend to the reqwest client builder
+    fn apply(self, reqwest_client_builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self.effective_backend() {
+            #[cfg(feature = "rustls-tls")]
+            TlsOption::RustlsTls => reqwest_client_builder.use_rustls_tls(),
+            #[cfg(feature = "native-tls")]
+            TlsOption::NativeTls => reqwest_client_builder.use_native_tls(),
+            #[allow(unreachable_patterns)]
+            _ => reqwest_client_builder,
+        }
+    }
+}
+
 /// Builds an [UnregisteredClient](crate::client::UnregisteredClient)
 pub struct ClientBuilder {
     rsa_key_size: Option<usize>,
+    existing_rsa_key: Option<RSAPrivKey>,
     server: Option<String>,
-    auth_token: Option<Secret<String>>,
+    server_port: Option<u16>,
+    use_tls: bool,
+    trailing_slash: bool,
+    endpoint_paths: EndpointPaths,
+    auth_token: Option<AuthToken>,
     proxies: Option<Vec<Proxy>>,
+    reqwest_client: Option<reqwest::Client>,
     timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    poll_timeout: Option<Duration>,
     ssl_verify: bool,
+    root_certificates: Vec<Certificate>,
+    root_certificate_pems: Vec<Vec<u8>>,
+    #[cfg(feature = "cert-pinning")]
+    pinned_cert_sha256: Option<[u8; 32]>,
+    tls_option: TlsOption,
     parse_logs: bool,
     dns_override: Option<IpAddr>,
+    retry_on_collision: bool,
+    auto_correlation: bool,
+    retry_on_parse_failure: bool,
+    sinks: Vec<Arc<dyn LogSink>>,
+    sink_error_policy: SinkErrorPolicy,
+    keepalive_interval: Option<Duration>,
+    user_agents: Vec<String>,
+    custom_headers: Vec<(HeaderName, HeaderValue)>,
+    request_customizer: Option<RequestCustomizer>,
+    correlation_generator: Option<CorrelationGenerator>,
+    register_payload_hook: Option<RegisterPayloadHook>,
+    rsa_padding: RsaPadding,
+    oaep_hash: OaepHash,
+    max_history: usize,
+    keygen_progress: Option<Box<dyn Fn(KeyGenPhase) + Send + Sync>>,
+    lazy_key: bool,
+    #[cfg(feature = "async-compat")]
+    deregister_on_drop: bool,
 }
 
 impl ClientBuilder {
@@ -40,13 +157,44 @@ impl ClientBuilder {
     pub fn new() -> Self {
         Self {
             rsa_key_size: None,
+            existing_rsa_key: None,
             server: None,
+            server_port: None,
+            use_tls: true,
+            trailing_slash: false,
+            endpoint_paths: EndpointPaths::default(),
             auth_token: None,
             proxies: None,
+            reqwest_client: None,
             timeout: None,
+            connect_timeout: None,
+            poll_timeout: None,
             ssl_verify: false,
+            root_certificates: Vec::new(),
+            root_certificate_pems: Vec::new(),
+            #[cfg(feature = "cert-pinning")]
+            pinned_cert_sha256: None,
+            tls_option: TlsOption::default(),
             parse_logs: true,
             dns_override: None,
+            retry_on_collision: false,
+            auto_correlation: false,
+            retry_on_parse_failure: false,
+            sinks: Vec::new(),
+            sink_error_policy: SinkErrorPolicy::default(),
+            keepalive_interval: None,
+            user_agents: Vec::new(),
+            custom_headers: Vec::new(),
+            request_customizer: None,
+            correlation_generator: None,
+            register_payload_hook: None,
+            rsa_padding: RsaPadding::default(),
+            oaep_hash: OaepHash::default(),
+            max_history: 0,
+            keygen_progress: None,
+            lazy_key: false,
+            #[cfg(feature = "async-compat")]
+            deregister_on_drop: false,
         }
     }
 
@@ -58,6 +206,18 @@ impl ClientBuilder {
         }
     }
 
+    /// Sets an existing RSA private key for the client to use instead of
+    /// generating a new one.
+    ///
+    /// This avoids the cost of generating a new key for every client, and
+    /// takes precedence over the RSA key size if both are set.
+    pub fn with_existing_rsa_key(self, rsa_key: RSAPrivKey) -> Self {
+        Self {
+            existing_rsa_key: Some(rsa_key),
+            ..self
+        }
+    }
+
     /// Sets the Interactsh server that the client will connect to.
     pub fn with_server(self, server: String) -> Self {
         Self {
@@ -66,24 +226,118 @@ impl ClientBuilder {
         }
     }
 
+    /// Sets the port of the Interactsh server that the client will connect to.
+    ///
+    /// Defaults to 443, or 80 if [TLS is disabled](ClientBuilder::use_tls()). This
+    /// port is also used for the [DNS override](ClientBuilder::set_dns_override())
+    /// if one is set.
+    pub fn with_server_port(self, server_port: u16) -> Self {
+        Self {
+            server_port: Some(server_port),
+            ..self
+        }
+    }
+
+    /// Sets whether the client will connect to the Interactsh server over
+    /// HTTPS (true) or plain HTTP (false).
+    ///
+    /// Defaults to true. Disabling TLS is only intended for testing against
+    /// a local Interactsh server.
+    pub fn use_tls(self, use_tls: bool) -> Self {
+        Self { use_tls, ..self }
+    }
+
+    /// Sets whether the client will append a trailing slash to the register,
+    /// deregister, and poll endpoint paths (ex. `/poll/` instead of `/poll`).
+    ///
+    /// Defaults to false. This is only needed for reverse proxies in front of
+    /// the server that require the trailing slash.
+    pub fn trailing_slash(self, trailing_slash: bool) -> Self {
+        Self {
+            trailing_slash,
+            ..self
+        }
+    }
+
+    /// Sets the path of the poll endpoint on the server (defaults to `/poll`).
+    ///
+    /// This is only needed when the server is behind a reverse proxy or API
+    /// gateway that serves the endpoints under other paths (ex.
+    /// `/interactsh/poll`). Leading and trailing slashes are ignored; see
+    /// [trailing_slash](ClientBuilder::trailing_slash()).
+    pub fn with_poll_path(self, poll_path: String) -> Self {
+        Self {
+            endpoint_paths: EndpointPaths {
+                poll: EndpointPaths::normalize(&poll_path),
+                ..self.endpoint_paths
+            },
+            ..self
+        }
+    }
+
+    /// Sets the path of the register endpoint on the server (defaults to
+    /// `/register`).
+    ///
+    /// See [with_poll_path](ClientBuilder::with_poll_path()).
+    pub fn with_register_path(self, register_path: String) -> Self {
+        Self {
+            endpoint_paths: EndpointPaths {
+                register: EndpointPaths::normalize(&register_path),
+                ..self.endpoint_paths
+            },
+            ..self
+        }
+    }
+
+    /// Sets the path of the deregister endpoint on the server (defaults to
+    /// `/deregister`).
+    ///
+    /// See [with_poll_path](ClientBuilder::with_poll_path()).
+    pub fn with_deregister_path(self, deregister_path: String) -> Self {
+        Self {
+            endpoint_paths: EndpointPaths {
+                deregister: EndpointPaths::normalize(&deregister_path),
+                ..self.endpoint_paths
+            },
+            ..self
+        }
+    }
+
     /// Sets an optional auth token that the client will use to authenticate
     /// with the Interactsh server.
     ///
-    /// If this is not set, then no auth header will be sent to the
-    /// server.
+    /// The token is sent as-is in the `Authorization` header. To use another
+    /// scheme, see [with_auth](ClientBuilder::with_auth()). If this is not
+    /// set, then no auth header will be sent to the server.
     pub fn with_auth_token(self, auth_token: String) -> Self {
-        let token = Secret::new(auth_token);
+        let token = AuthToken::SimpleAuth(Secret::new(auth_token));
         Self {
             auth_token: Some(token),
             ..self
         }
     }
 
+    /// Sets an optional auth token that the client will use to authenticate
+    /// with the Interactsh server, along with how it is sent in the
+    /// `Authorization` header.
+    ///
+    /// If this is not set, then no auth header will be sent to the
+    /// server.
+    pub fn with_auth(self, auth_token: AuthToken) -> Self {
+        Self {
+            auth_token: Some(auth_token),
+            ..self
+        }
+    }
+
     /// Sets an optional proxy that the client can use.
     ///
     /// This can be set more than once; each new proxy will be added
     /// to a list of proxies that the client will try. Proxies will be
     /// tried in the order added.
+    ///
+    /// SOCKS proxies (`socks5://` and `socks5h://` URLs) require the
+    /// `socks` (or `socks-proxy`) feature flag to be enabled.
     pub fn with_proxy(self, proxy: Proxy) -> Self {
         let proxies = match self.proxies {
             Some(mut proxies) => {
@@ -96,6 +350,21 @@ impl ClientBuilder {
         Self { proxies, ..self }
     }
 
+    /// Sets an existing reqwest client for the client to use instead of
+    /// building its own.
+    ///
+    /// When this is set, the builder options that configure the reqwest
+    /// client are ignored: the proxies, timeouts, SSL verification, and DNS
+    /// override must be set on the provided client instead. The
+    /// [poll timeout](ClientBuilder::with_poll_timeout()) is set per request,
+    /// so it is still applied.
+    pub fn with_reqwest_client(self, reqwest_client: reqwest::Client) -> Self {
+        Self {
+            reqwest_client: Some(reqwest_client),
+            ..self
+        }
+    }
+
     /// Sets the timeout value for server requests.
     pub fn with_timeout(self, timeout: Duration) -> Self {
         Self {
@@ -104,12 +373,117 @@ impl ClientBuilder {
         }
     }
 
+    /// Sets the timeout value for connecting to the server.
+    ///
+    /// This only covers the connect phase, so it can be kept short to fail
+    /// fast when the server is unreachable, while the
+    /// [client-wide timeout](ClientBuilder::with_timeout()) still bounds the
+    /// whole request. It applies to the register, poll, and deregister
+    /// requests.
+    pub fn with_connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            connect_timeout: Some(connect_timeout),
+            ..self
+        }
+    }
+
+    /// Sets a separate timeout value for poll requests.
+    ///
+    /// Registration can take longer than a poll, so this allows a shorter
+    /// timeout for polling without affecting the register and deregister
+    /// requests. If this is not set, poll requests use the
+    /// [client-wide timeout](ClientBuilder::with_timeout()).
+    pub fn with_poll_timeout(self, poll_timeout: Duration) -> Self {
+        Self {
+            poll_timeout: Some(poll_timeout),
+            ..self
+        }
+    }
+
     /// Sets whether or not the client should verify the
     /// server's SSL certificate.
     pub fn verify_ssl(self, ssl_verify: bool) -> Self {
         Self { ssl_verify, ..self }
     }
 
+    /// Adds a root certificate that the client trusts when verifying the
+    /// server's SSL certificate, such as the certificate of the private CA
+    /// of a self-hosted server.
+    ///
+    /// This also turns on [verify_ssl](ClientBuilder::verify_ssl()), since
+    /// the certificate is only used when the server's certificate is
+    /// verified. This can be called multiple times to add multiple
+    /// certificates.
+    pub fn with_root_certificate(self, certificate: Certificate) -> Self {
+        let mut root_certificates = self.root_certificates;
+        root_certificates.push(certificate);
+
+        Self {
+            root_certificates,
+            ssl_verify: true,
+            ..self
+        }
+    }
+
+    /// Adds a PEM encoded root certificate that the client trusts when
+    /// verifying the server's SSL certificate.
+    ///
+    /// The certificate is parsed when the client is built. Like
+    /// [with_root_certificate](ClientBuilder::with_root_certificate()), this
+    /// turns on [verify_ssl](ClientBuilder::verify_ssl()).
+    pub fn with_root_certificate_pem(self, certificate_pem: &[u8]) -> Self {
+        let mut root_certificate_pems = self.root_certificate_pems;
+        root_certificate_pems.push(certificate_pem.to_vec());
+
+        Self {
+            root_certificate_pems,
+            ssl_verify: true,
+            ..self
+        }
+    }
+
+    /// Pins the server's certificate, so that the client only connects to a
+    /// server presenting a leaf certificate with the provided SHA-256 hash
+    /// (of its DER encoding).
+    ///
+    /// The certificate chain is not checked when a certificate is pinned, so
+    /// this takes precedence over [verify_ssl](ClientBuilder::verify_ssl())
+    /// and any [root certificates](ClientBuilder::with_root_certificate()).
+    /// This always uses the Rustls backend, and requires the `cert-pinning`
+    /// feature flag.
+    #[cfg(feature = "cert-pinning")]
+    pub fn with_pinned_cert_sha256(self, cert_sha256: [u8; 32]) -> Self {
+        Self {
+            pinned_cert_sha256: Some(cert_sha256),
+            ..self
+        }
+    }
+
+    /// Sets the client to use [Rustls](https://github.com/rustls/rustls) (the
+    /// default).
+    ///
+    /// Only available when both the `rustls-tls` and `native-tls` feature
+    /// flags are enabled.
+    #[cfg(all(feature = "rustls-tls", feature = "native-tls"))]
+    pub fn use_rustls_tls(self) -> Self {
+        Self {
+            tls_option: TlsOption::RustlsTls,
+            ..self
+        }
+    }
+
+    /// Sets the client to use the OS native TLS library.
+    ///
+    /// Only available when both the `rustls-tls` and `native-tls` feature
+    /// flags are enabled.
+    #[cfg(all(feature = "rustls-tls", feature = "native-tls"))]
+    pub fn use_native_tls(self) -> Self {
+        Self {
+            tls_option: TlsOption::NativeTls,
+            ..self
+        }
+    }
+
     /// Sets whether or not the client should parse the logs
     /// or just return the raw logs.
     pub fn parse_logs(self, parse_logs: bool) -> Self {
@@ -126,40 +500,448 @@ impl ClientBuilder {
         }
     }
 
+    /// Sets whether or not the client should generate a new subdomain and
+    /// correlation ID and try again if the server reports that the
+    /// correlation ID is already in use during registration.
+    ///
+    /// The registration will be retried a limited number of times before
+    /// giving up and returning the error.
+    pub fn retry_on_collision(self, retry_on_collision: bool) -> Self {
+        Self {
+            retry_on_collision,
+            ..self
+        }
+    }
+
+    /// Sets whether or not the client should use the correlation settings
+    /// reported by the server during registration.
+    ///
+    /// When set, [register](crate::client::UnregisteredClient::register())
+    /// reads the correlation ID and nonce lengths from the register response.
+    /// If they don't match the lengths used by the client, the client
+    /// generates a new subdomain and correlation ID with the server's lengths
+    /// and registers again. If the server doesn't report its settings,
+    /// registration fails with
+    /// [CorrelationDiscoveryUnsupported](crate::errors::RegistrationError::CorrelationDiscoveryUnsupported).
+    pub fn auto_correlation(self, auto_correlation: bool) -> Self {
+        Self {
+            auto_correlation,
+            ..self
+        }
+    }
+
+    /// Sets whether or not the client should retry a poll once if the
+    /// server response could not be read or parsed as JSON.
+    ///
+    /// This helps with flaky connections that occasionally truncate the
+    /// response body. If the retry also fails, the error is returned.
+    pub fn retry_on_parse_failure(self, retry_on_parse_failure: bool) -> Self {
+        Self {
+            retry_on_parse_failure,
+            ..self
+        }
+    }
+
+    /// Adds a [LogSink] that the client will pass each polled log to.
+    ///
+    /// This can be set more than once; each log is passed to the sinks
+    /// in the order they were added. How sink errors are handled is set by
+    /// [sink_error_policy](ClientBuilder::sink_error_policy()).
+    pub fn with_sink(self, sink: Arc<dyn LogSink>) -> Self {
+        let mut sinks = self.sinks;
+        sinks.push(sink);
+
+        Self { sinks, ..self }
+    }
+
+    /// Sets how the client handles errors returned by its [LogSink]s.
+    ///
+    /// Defaults to [SinkErrorPolicy::ContinueOthers], which ignores sink
+    /// errors. [SinkErrorPolicy::StopPoll] returns a
+    /// [SinkFailure](crate::errors::ClientPollError::SinkFailure) error from
+    /// the poll that holds the polled logs.
+    pub fn sink_error_policy(self, sink_error_policy: SinkErrorPolicy) -> Self {
+        Self {
+            sink_error_policy,
+            ..self
+        }
+    }
+
+    /// Sets an interval for the client to send a lightweight HEAD request
+    /// to the server while waiting between polls.
+    ///
+    /// This keeps the connection to the server open for servers that close
+    /// idle connections quickly, so the next poll does not need a new TLS
+    /// handshake. The keepalive requests are only sent while waiting in
+    /// [wait_for_next_poll](crate::client::RegisteredClient::wait_for_next_poll()).
+    pub fn keepalive_ping(self, keepalive_interval: Duration) -> Self {
+        Self {
+            keepalive_interval: Some(keepalive_interval),
+            ..self
+        }
+    }
+
+    /// Sets a list of User-Agent headers that the client will cycle
+    /// through, using the next one in the list for each request sent
+    /// to the server.
+    ///
+    /// If this is not set (or the list is empty), the default User-Agent
+    /// of the underlying HTTP client is used.
+    pub fn with_rotating_user_agents(self, user_agents: Vec<String>) -> Self {
+        Self {
+            user_agents,
+            ..self
+        }
+    }
+
+    /// Adds a custom header that the client will send with every request to
+    /// the server.
+    ///
+    /// This can be called multiple times to add more headers. Headers with
+    /// the same name are all sent rather than replacing each other. The
+    /// [auth token](ClientBuilder::with_auth_token()) is still sent if set.
+    pub fn with_header(self, name: HeaderName, value: HeaderValue) -> Self {
+        let mut custom_headers = self.custom_headers;
+        custom_headers.push((name, value));
+
+        Self {
+            custom_headers,
+            ..self
+        }
+    }
+
+    /// Sets a function that can customize every request to the server right
+    /// before it is sent, such as signing the request or adding per-request
+    /// tracing headers.
+    ///
+    /// Headers set by the function replace the auth token, User-Agent, and
+    /// [custom headers](ClientBuilder::with_header()) of the same name, which
+    /// are added to the request after the function runs.
+    pub fn with_request_customizer<F>(self, customizer: F) -> Self
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder + Send + Sync + 'static,
+    {
+        Self {
+            request_customizer: Some(RequestCustomizer::new(customizer)),
+            ..self
+        }
+    }
+
+    /// Sets a function that generates the subdomain and correlation ID of the
+    /// client, returned as `(subdomain, correlation_id)`, instead of the
+    /// default random lowercase alphanumeric ones.
+    ///
+    /// This can be used for deterministic or prefix-tagged subdomains. The
+    /// generated values must have the lengths given by the
+    /// [CorrelationConfig] passed to the function, otherwise building (or
+    /// regenerating the values after a
+    /// [collision](ClientBuilder::retry_on_collision())) fails. The
+    /// correlation ID should also be the prefix of the subdomain, since the
+    /// server uses it to correlate interactions with the client.
+    pub fn with_correlation_generator<F>(self, generator: F) -> Self
+    where
+        F: Fn(&CorrelationConfig) -> (String, String) + Send + Sync + 'static,
+    {
+        Self {
+            correlation_generator: Some(CorrelationGenerator::new(generator)),
+            ..self
+        }
+    }
+
+    /// Sets a hook that can modify the serialized register request body
+    /// right before it is sent to the server.
+    ///
+    /// This is an advanced option meant for working with non-standard
+    /// Interactsh server variants that expect extra or renamed fields.
+    pub fn with_register_payload_hook<F>(self, hook: F) -> Self
+    where
+        F: Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    {
+        Self {
+            register_payload_hook: Some(RegisterPayloadHook::new(hook)),
+            ..self
+        }
+    }
+
+    /// Sets the padding scheme the server uses to encrypt the AES key
+    /// with the client's RSA public key.
+    ///
+    /// Defaults to [RsaPadding::Oaep], which is used by the official
+    /// Interactsh server. Set this to [RsaPadding::Pkcs1v15] to work with
+    /// older server forks that use PKCS#1 v1.5 padding.
+    pub fn rsa_padding(self, rsa_padding: RsaPadding) -> Self {
+        Self {
+            rsa_padding,
+            ..self
+        }
+    }
+
+    /// Sets the hash algorithm the server uses with OAEP padding when
+    /// encrypting the AES key.
+    ///
+    /// Defaults to [OaepHash::Sha256], which is used by the official
+    /// Interactsh server. This is ignored if the
+    /// [RSA padding](ClientBuilder::rsa_padding()) is not OAEP.
+    pub fn with_oaep_hash(self, oaep_hash: OaepHash) -> Self {
+        Self { oaep_hash, ..self }
+    }
+
+    /// Sets the maximum number of polled logs that the client will retain
+    /// in its [history](crate::client::RegisteredClient::history()).
+    ///
+    /// Once the history is full, the oldest logs are evicted. Defaults to 0,
+    /// which disables the history.
+    pub fn max_history(self, max_history: usize) -> Self {
+        Self {
+            max_history,
+            ..self
+        }
+    }
+
+    /// Sets a callback that is run when the builder starts and finishes
+    /// generating the RSA key for the client.
+    ///
+    /// Generating a large key can take a noticeable amount of time, so this
+    /// can be used to show progress in interactive tools. The backends don't
+    /// report progress during generation, so only the start and end are
+    /// signaled. The callback isn't run if an
+    /// [existing RSA key](ClientBuilder::with_existing_rsa_key()) is used.
+    pub fn with_keygen_progress<F>(self, keygen_progress: F) -> Self
+    where
+        F: Fn(KeyGenPhase) + Send + Sync + 'static,
+    {
+        Self {
+            keygen_progress: Some(Box::new(keygen_progress)),
+            ..self
+        }
+    }
+
+    /// Sets whether generating the RSA key is deferred until the client
+    /// registers (defaults to false).
+    ///
+    /// Generating a key can take a noticeable amount of time, which adds up
+    /// when building many clients up front. With this set,
+    /// [build](ClientBuilder::build()) skips generating the key, and
+    /// [register](crate::client::UnregisteredClient::register()) generates it
+    /// on a separate thread instead, returning an error if that fails. The
+    /// [keygen progress](ClientBuilder::with_keygen_progress()) callback isn't
+    /// run for deferred keys. This has no effect if an
+    /// [existing RSA key](ClientBuilder::with_existing_rsa_key()) is used.
+    pub fn lazy_key(self, lazy_key: bool) -> Self {
+        Self { lazy_key, ..self }
+    }
+
+    /// Sets whether the client will make a best-effort attempt to deregister
+    /// from the server when it is dropped without calling
+    /// [deregister](crate::client::RegisteredClient::deregister()).
+    ///
+    /// Defaults to false. Clones of a client share the same session, so the
+    /// request is only sent once the last clone is dropped. Since dropping
+    /// can't wait on an async request, the request is sent on a detached
+    /// thread and any errors are ignored; if the process exits right after
+    /// the client is dropped, the request may never be sent. Exporting the
+    /// [session](crate::client::RegisteredClient::export_session()) of the
+    /// client turns this off, so that the exported session keeps working.
+    ///
+    /// Requires the `async-compat` feature flag.
+    #[cfg(feature = "async-compat")]
+    pub fn deregister_on_drop(self, deregister_on_drop: bool) -> Self {
+        Self {
+            deregister_on_drop,
+            ..self
+        }
+    }
+
     /// Builds an [UnregisteredClient](crate::client::UnregisteredClient).
     ///
-    /// The server must be set and the RSA key generated in order for
-    /// this to succeed. If the build succeeds, the
+    /// The server and either the RSA key size or an existing RSA key
+    /// must be set in order for this to succeed. If the build succeeds, the
     /// register function must be called on the returned
     ///  [UnregisteredClient](crate::client::UnregisteredClient)
     /// to turn it into a [RegisteredClient](crate::client::RegisteredClient).
-    pub fn build(self) -> Result<UnregisteredClient, ClientBuildError> {
-        // Ensure rsa_key and server are set
-        let rsa_key_size = self
-            .rsa_key_size
-            .context(client_build_error::MissingRsaKeySize)?;
-        let server = self.server.context(client_build_error::MissingServer)?;
+    pub fn build(mut self) -> Result<UnregisteredClient, ClientBuildError> {
+        // Ensure the server is set
+        let server = self
+            .server
+            .clone()
+            .context(client_build_error::MissingServer)?;
 
         // Get the other values needed
-        let rsa_key = RSAPrivKey::generate(rsa_key_size).context(client_build_error::RsaGen)?;
-        let pubkey = rsa_key
-            .get_pub_key()
-            .context(client_build_error::PubKeyExtract)?;
+        let rsa_key = match self.existing_rsa_key.take() {
+            Some(rsa_key) => ready_rsa_key(rsa_key)?,
+            None => {
+                let rsa_key_size = self
+                    .rsa_key_size
+                    .context(client_build_error::MissingRsaKeySize)?;
+
+                if self.lazy_key {
+                    RsaKeyState::Deferred {
+                        key_size: rsa_key_size,
+                    }
+                } else {
+                    self.report_keygen_progress(KeyGenPhase::Started {
+                        key_size: rsa_key_size,
+                    });
+                    let rsa_gen_result = RSAPrivKey::generate(rsa_key_size);
+                    self.report_keygen_progress(KeyGenPhase::Finished);
+
+                    ready_rsa_key(rsa_gen_result.context(client_build_error::RsaGen)?)?
+                }
+            }
+        };
         let secret = Uuid::new_v4().to_string();
-        let encoded_pub_key = pubkey
-            .b64_encode()
-            .context(client_build_error::PubKeyEncode)?;
+        let correlation_data = CorrelationData::generate_with(
+            self.correlation_generator.as_ref(),
+            &CorrelationConfig::default(),
+        );
+        ensure!(
+            correlation_data.has_valid_lengths(&CorrelationConfig::default()),
+            client_build_error::InvalidCorrelationData {
+                subdomain_len: correlation_data.sub_domain.len(),
+                correlation_id_len: correlation_data.correlation_id.len(),
+            }
+        );
+
+        self.build_client(rsa_key, server, Secret::new(secret), correlation_data)
+    }
+
+    /// Builds an [UnregisteredClient](crate::client::UnregisteredClient),
+    /// generating the RSA key on a separate thread.
+    ///
+    /// This is the same as [build](ClientBuilder::build()), except that the
+    /// async executor isn't blocked while the key is generated. This works
+    /// with any async runtime.
+    pub async fn build_async(self) -> Result<UnregisteredClient, ClientBuildError> {
+        let generates_key =
+            self.existing_rsa_key.is_none() && !self.lazy_key && self.server.is_some();
+        let rsa_key_size = match self.rsa_key_size {
+            Some(rsa_key_size) if generates_key => rsa_key_size,
+            _ => return self.build(),
+        };
 
-        let sub_domain = Alphanumeric
-            .sample_string(&mut thread_rng(), 33)
-            .to_ascii_lowercase();
-        let mut correlation_id = sub_domain.clone();
-        correlation_id.truncate(20);
+        self.report_keygen_progress(KeyGenPhase::Started {
+            key_size: rsa_key_size,
+        });
+        let rsa_gen_result =
+            blocking_task::run_blocking(move || RSAPrivKey::generate(rsa_key_size)).await;
+        self.report_keygen_progress(KeyGenPhase::Finished);
 
-        // Build the reqwest client
+        let rsa_key = rsa_gen_result.context(client_build_error::RsaGen)?;
+
+        self.with_existing_rsa_key(rsa_key).build()
+    }
+
+    /// Builds a blocking [UnregisteredClient](crate::client::blocking::UnregisteredClient)
+    /// for use in synchronous code.
+    ///
+    /// This is the same as [build](ClientBuilder::build()), except that the
+    /// client runs its requests on an internal runtime (see the
+    /// [blocking](crate::client::blocking) module).
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<blocking::UnregisteredClient, ClientBuildError> {
+        self.build().and_then(blocking::UnregisteredClient::new)
+    }
+
+    /// Builds a [RegisteredClient](crate::client::RegisteredClient) from a
+    /// previously exported [SessionState] without registering with the
+    /// server again.
+    ///
+    /// This is used in place of [build](ClientBuilder::build()). The RSA key,
+    /// server, and correlation data are taken from the session (so the RSA key
+    /// size and server set on this builder are ignored); all other options are
+    /// taken from this builder.
+    pub fn with_session(self, session: SessionState) -> Result<RegisteredClient, ClientBuildError> {
+        let rsa_key = RSAPrivKey::from_pkcs8_pem(session.rsa_key_pem.expose_secret())
+            .context(client_build_error::SessionKeyImport)?;
+        let correlation_data = CorrelationData {
+            sub_domain: session.sub_domain,
+            correlation_id: session.correlation_id,
+        };
+
+        let unreg_client = self.build_client(
+            ready_rsa_key(rsa_key.clone())?,
+            session.server,
+            session.secret_key,
+            correlation_data,
+        )?;
+
+        Ok(unreg_client.into_registered_with_key(rsa_key))
+    }
+
+    /// Runs the keygen progress callback, if one was set
+    fn report_keygen_progress(&self, phase: KeyGenPhase) {
+        if let Some(keygen_progress) = &self.keygen_progress {
+            keygen_progress(phase);
+        }
+    }
+
+    /// Builds the client with the provided key, server, and correlation data,
+    /// using the options set on this builder
+    fn build_client(
+        mut self,
+        rsa_key: RsaKeyState,
+        server: String,
+        secret_key: Secret<String>,
+        correlation_data: CorrelationData,
+    ) -> Result<UnregisteredClient, ClientBuildError> {
+        let server_port = match (self.server_port, self.use_tls) {
+            (Some(server_port), _) => server_port,
+            (None, true) => DEFAULT_SERVER_PORT,
+            (None, false) => DEFAULT_PLAINTEXT_SERVER_PORT,
+        };
+
+        let reqwest_client = match self.reqwest_client.take() {
+            Some(reqwest_client) => reqwest_client,
+            None => self.build_reqwest_client(&server, server_port)?,
+        };
+
+        // Create the UnregisteredClient object
+        let server_url = http_utils::server_base_url(&server, server_port, self.use_tls);
+        let correlation_config = CorrelationConfig::default();
+        let unreg_client = UnregisteredClient {
+            rsa_key,
+            server,
+            server_url,
+            trailing_slash: self.trailing_slash,
+            endpoint_paths: self.endpoint_paths,
+            sub_domain: correlation_data.sub_domain,
+            correlation_id: correlation_data.correlation_id,
+            auth_token: self.auth_token,
+            secret_key,
+            reqwest_client,
+            parse_logs: self.parse_logs,
+            retry_on_collision: self.retry_on_collision,
+            auto_correlation: self.auto_correlation,
+            correlation_config,
+            retry_on_parse_failure: self.retry_on_parse_failure,
+            log_sinks: LogSinks::new(self.sinks, self.sink_error_policy),
+            keepalive_interval: self.keepalive_interval,
+            poll_timeout: self.poll_timeout,
+            user_agents: UserAgentRotation::new(self.user_agents),
+            custom_headers: self.custom_headers,
+            request_customizer: self.request_customizer,
+            correlation_generator: self.correlation_generator,
+            register_payload_hook: self.register_payload_hook,
+            rsa_padding: self.rsa_padding,
+            oaep_hash: self.oaep_hash,
+            max_history: self.max_history,
+            #[cfg(feature = "async-compat")]
+            deregister_on_drop: self.deregister_on_drop,
+        };
+
+        Ok(unreg_client)
+    }
+
+    /// Builds the reqwest client used to send requests to the server from
+    /// the options set on this builder
+    fn build_reqwest_client(
+        &mut self,
+        server: &str,
+        server_port: u16,
+    ) -> Result<reqwest::Client, ClientBuildError> {
         let mut reqwest_client_builder = reqwest::Client::builder();
 
-        reqwest_client_builder = match self.proxies {
+        reqwest_client_builder = match self.proxies.take() {
             None => reqwest_client_builder,
             Some(proxies) => {
                 let mut builder = reqwest_client_builder;
@@ -175,10 +957,25 @@ impl ClientBuilder {
         let timeout = self.timeout.unwrap_or(Duration::from_secs(15));
         reqwest_client_builder = reqwest_client_builder.timeout(timeout);
 
-        cfg_if::cfg_if! {
-            if #[cfg(all(feature = "reqwest-rustls-tls", feature = "reqwest-native-tls"))] {
-                reqwest_client_builder = reqwest_client_builder.use_rustls_tls();
-            }
+        if let Some(connect_timeout) = self.connect_timeout {
+            reqwest_client_builder = reqwest_client_builder.connect_timeout(connect_timeout);
+        }
+
+        reqwest_client_builder = self.tls_option.apply(reqwest_client_builder);
+
+        for certificate_pem in self.root_certificate_pems.iter() {
+            let certificate = Certificate::from_pem(certificate_pem)
+                .context(client_build_error::RootCertificateParse)?;
+            reqwest_client_builder = reqwest_client_builder.add_root_certificate(certificate);
+        }
+        for certificate in self.root_certificates.drain(..) {
+            reqwest_client_builder = reqwest_client_builder.add_root_certificate(certificate);
+        }
+
+        #[cfg(feature = "cert-pinning")]
+        if let Some(pinned_cert_sha256) = self.pinned_cert_sha256 {
+            let tls_config = cert_pinning::pinned_tls_config(pinned_cert_sha256);
+            reqwest_client_builder = reqwest_client_builder.use_preconfigured_tls(tls_config);
         }
 
         reqwest_client_builder =
@@ -186,31 +983,31 @@ impl ClientBuilder {
 
         reqwest_client_builder = match self.dns_override {
             Some(server_ip_address) => {
-                let socket_addr = SocketAddr::new(server_ip_address, 443);
-                reqwest_client_builder.resolve(server.as_str(), socket_addr)
+                let socket_addr = SocketAddr::new(server_ip_address, server_port);
+                reqwest_client_builder.resolve(server, socket_addr)
             }
             None => reqwest_client_builder,
         };
 
-        let reqwest_client = reqwest_client_builder
+        reqwest_client_builder
             .build()
-            .context(client_build_error::ReqwestBuildFailed)?;
+            .context(client_build_error::ReqwestBuildFailed)
+    }
+}
 
-        // Create the UnregisteredClient object
-        let unreg_client = UnregisteredClient {
-            rsa_key,
-            server,
-            sub_domain,
-            correlation_id,
-            auth_token: self.auth_token,
-            secret_key: Secret::new(secret),
-            encoded_pub_key,
-            reqwest_client,
-            parse_logs: self.parse_logs,
-        };
+/// Encodes the public key of a generated or provided RSA key for registering
+fn ready_rsa_key(rsa_key: RSAPrivKey) -> Result<RsaKeyState, ClientBuildError> {
+    let pubkey = rsa_key
+        .get_pub_key()
+        .context(client_build_error::PubKeyExtract)?;
+    let encoded_pub_key = pubkey
+        .b64_encode()
+        .context(client_build_error::PubKeyEncode)?;
 
-        Ok(unreg_client)
-    }
+    Ok(RsaKeyState::Ready {
+        rsa_key: Box::new(rsa_key),
+        encoded_pub_key,
+    })
 }
 
 impl Default for ClientBuilder {
@@ -227,17 +1024,81 @@ impl Default for ClientBuilder {
 
         Self {
             rsa_key_size: Some(2048),
+            existing_rsa_key: None,
             server: Some(server.to_string()),
+            server_port: None,
+            use_tls: true,
+            trailing_slash: false,
+            endpoint_paths: EndpointPaths::default(),
             auth_token: None,
             proxies: None,
+            reqwest_client: None,
             timeout: Some(Duration::from_secs(15)),
+            connect_timeout: None,
+            poll_timeout: None,
             ssl_verify: false,
+            root_certificates: Vec::new(),
+            root_certificate_pems: Vec::new(),
+            #[cfg(feature = "cert-pinning")]
+            pinned_cert_sha256: None,
+            tls_option: TlsOption::default(),
             parse_logs: true,
             dns_override: None,
+            retry_on_collision: false,
+            auto_correlation: false,
+            retry_on_parse_failure: false,
+            sinks: Vec::new(),
+            sink_error_policy: SinkErrorPolicy::default(),
+            keepalive_interval: None,
+            user_agents: Vec::new(),
+            custom_headers: Vec::new(),
+            request_customizer: None,
+            correlation_generator: None,
+            register_payload_hook: None,
+            rsa_padding: RsaPadding::default(),
+            oaep_hash: OaepHash::default(),
+            max_history: 0,
+            keygen_progress: None,
+            lazy_key: false,
+            #[cfg(feature = "async-compat")]
+            deregister_on_drop: false,
         }
     }
 }
 
+/// Builds a client with the [default options](ClientBuilder::default()) and
+/// registers it with a randomly picked default server, returning a client
+/// that is ready to poll.
+///
+/// This is a shortcut for quick scripts. Use a [ClientBuilder] to set any
+/// other options.
+pub async fn quick_client() -> Result<RegisteredClient, QuickClientError> {
+    register_quick_client(ClientBuilder::default()).await
+}
+
+/// Same as [quick_client], but registers with the provided server instead
+/// of a default server.
+pub async fn quick_client_with_server(
+    server: String,
+) -> Result<RegisteredClient, QuickClientError> {
+    register_quick_client(ClientBuilder::default().with_server(server)).await
+}
+
+/// Builds the client without blocking the executor, then registers it
+async fn register_quick_client(
+    builder: ClientBuilder,
+) -> Result<RegisteredClient, QuickClientError> {
+    let client = builder
+        .build_async()
+        .await
+        .context(quick_client_error::Build)?;
+
+    client
+        .register()
+        .await
+        .context(quick_client_error::Register)
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -245,6 +1106,8 @@ mod tests {
     use rand::{Rng, RngCore};
 
     use super::*;
+    use crate::client::http_utils::Client;
+    use crate::client::test_cert::TEST_CERT_PEM;
 
     #[test]
     fn default_build_succeeds() {
@@ -281,22 +1144,339 @@ mod tests {
 
         // Get a random duration in seconds
         let duration_secs = rng.gen_range(5..=30);
+        let keepalive_secs = rng.gen_range(1..=10);
 
         // Generate boolean values
         let verify_ssl = rng.gen_bool(1.0 / 2.0);
         let parse_logs = rng.gen_bool(1.0 / 2.0);
+        let retry_on_collision = rng.gen_bool(1.0 / 2.0);
+        let retry_on_parse_failure = rng.gen_bool(1.0 / 2.0);
 
         let _builder = ClientBuilder::new()
             .with_server("oast.pro".into())
             .with_rsa_key_size(2048)
+            .with_server_port(8443)
+            .use_tls(true)
             .with_auth_token(token)
             .with_timeout(Duration::from_secs(duration_secs))
+            .with_connect_timeout(Duration::from_secs(3))
+            .with_poll_timeout(Duration::from_secs(5))
             .verify_ssl(verify_ssl)
             .parse_logs(parse_logs)
+            .retry_on_collision(retry_on_collision)
+            .retry_on_parse_failure(retry_on_parse_failure)
+            .keepalive_ping(Duration::from_secs(keepalive_secs))
+            .with_rotating_user_agents(vec!["agent-one".into(), "agent-two".into()])
+            .with_header(
+                HeaderName::from_static("x-api-key"),
+                HeaderValue::from_static("api-key"),
+            )
+            .with_request_customizer(|http_request| http_request.header("x-trace-id", "trace-id"))
+            .rsa_padding(RsaPadding::Pkcs1v15)
+            .with_oaep_hash(OaepHash::Sha512)
+            .max_history(100)
             .build()
             .expect("Build with all options failed");
     }
 
+    #[test]
+    fn build_with_server_port_sets_server_url() {
+        let client = ClientBuilder::new()
+            .with_server("interactsh.local".into())
+            .with_rsa_key_size(2048)
+            .with_server_port(8443)
+            .build()
+            .expect("Build with a server port failed");
+
+        assert_eq!(client.server_url, "https://interactsh.local:8443");
+    }
+
+    #[test]
+    fn build_with_trailing_slash_appends_slash_to_endpoints() {
+        let client = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .trailing_slash(true)
+            .build()
+            .expect("Build with trailing slash failed");
+
+        assert_eq!(client.get_registration_url(), "https://oast.pro/register/");
+        assert_eq!(
+            client
+                .into_registered()
+                .expect("Failed to convert the client")
+                .get_registration_url(),
+            "https://oast.pro/deregister/"
+        );
+    }
+
+    #[test]
+    fn build_with_custom_paths_uses_paths_for_endpoints() {
+        let client = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .with_register_path("/interactsh/register".into())
+            .with_deregister_path("interactsh/deregister/".into())
+            .build()
+            .expect("Build with custom paths failed");
+
+        assert_eq!(
+            client.get_registration_url(),
+            "https://oast.pro/interactsh/register"
+        );
+        assert_eq!(client.endpoint_paths.poll, "poll");
+        assert_eq!(
+            client
+                .into_registered()
+                .expect("Failed to convert the client")
+                .get_registration_url(),
+            "https://oast.pro/interactsh/deregister"
+        );
+    }
+
+    #[test]
+    fn tls_option_falls_back_to_enabled_backend() {
+        cfg_if::cfg_if! {
+            if #[cfg(all(feature = "rustls-tls", feature = "native-tls"))] {
+                assert_eq!(TlsOption::NativeTls.effective_backend(), TlsOption::NativeTls);
+                assert_eq!(TlsOption::RustlsTls.effective_backend(), TlsOption::RustlsTls);
+            } else if #[cfg(feature = "rustls-tls")] {
+                assert_eq!(TlsOption::NativeTls.effective_backend(), TlsOption::RustlsTls);
+            } else {
+                assert_eq!(TlsOption::RustlsTls.effective_backend(), TlsOption::NativeTls);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn quick_client_returns_registration_error() {
+        let quick_client_result = quick_client_with_server("127.0.0.1".into()).await;
+
+        assert!(matches!(
+            quick_client_result,
+            Err(QuickClientError::Register { .. })
+        ));
+    }
+
+    #[test]
+    fn build_uses_correlation_generator() {
+        let client = ClientBuilder::default()
+            .with_correlation_generator(|config| {
+                let sub_domain = "t".repeat(config.subdomain_length());
+                let correlation_id = "t".repeat(config.correlation_id_length());
+
+                (sub_domain, correlation_id)
+            })
+            .build()
+            .expect("Build with correlation generator failed");
+        let invalid_build_result = ClientBuilder::default()
+            .with_correlation_generator(|_| ("short".into(), "sh".into()))
+            .build();
+
+        assert_eq!(client.sub_domain, "t".repeat(33));
+        assert!(matches!(
+            invalid_build_result,
+            Err(ClientBuildError::InvalidCorrelationData {
+                subdomain_len: 5,
+                correlation_id_len: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn build_with_root_certificate_pem_succeeds() {
+        let _client = ClientBuilder::default()
+            .with_root_certificate_pem(TEST_CERT_PEM.as_bytes())
+            .build()
+            .expect("Build with a root certificate failed");
+    }
+
+    #[cfg(feature = "cert-pinning")]
+    #[test]
+    fn build_with_pinned_cert_succeeds() {
+        use sha2::{Digest, Sha256};
+
+        let cert_sha256 = Sha256::digest(crate::client::test_cert::test_cert_der()).into();
+
+        let _client = ClientBuilder::default()
+            .with_pinned_cert_sha256(cert_sha256)
+            .build()
+            .expect("Build with a pinned certificate failed");
+    }
+
+    #[test]
+    fn adding_a_root_certificate_turns_on_ssl_verification() {
+        let builder = ClientBuilder::default().with_root_certificate_pem(TEST_CERT_PEM.as_bytes());
+
+        assert!(builder.ssl_verify);
+    }
+
+    #[test]
+    fn build_without_tls_uses_http_server_url() {
+        let client = ClientBuilder::new()
+            .with_server("localhost".into())
+            .with_rsa_key_size(2048)
+            .use_tls(false)
+            .build()
+            .expect("Build without TLS failed");
+
+        assert_eq!(client.server_url, "http://localhost");
+    }
+
+    #[tokio::test]
+    async fn connect_timeout_bounds_registration_connect() {
+        // 10.255.255.1 is not routable, so the connect hangs until a timeout
+        let client = ClientBuilder::new()
+            .with_server("10.255.255.1".into())
+            .with_rsa_key_size(2048)
+            .use_tls(false)
+            .with_timeout(Duration::from_secs(60))
+            .with_connect_timeout(Duration::from_millis(500))
+            .build()
+            .expect("Build with a connect timeout failed");
+
+        let start = std::time::Instant::now();
+        let register_result = client.register().await;
+
+        assert!(register_result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn build_with_reqwest_client_succeeds() {
+        let reqwest_client = reqwest::Client::builder()
+            .pool_max_idle_per_host(1)
+            .build()
+            .expect("Failed to build the reqwest client");
+
+        let _builder = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .with_reqwest_client(reqwest_client)
+            .with_proxy(Proxy::all("http://127.0.0.1:8080").expect("Invalid HTTP proxy URL"))
+            .build()
+            .expect("Build with a reqwest client failed");
+    }
+
+    #[test]
+    fn build_with_proxies_succeeds() {
+        let http_proxy = Proxy::all("http://127.0.0.1:8080").expect("Invalid HTTP proxy URL");
+        let https_proxy = Proxy::https("http://127.0.0.1:8443").expect("Invalid HTTPS proxy URL");
+
+        let _builder = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .with_proxy(https_proxy)
+            .with_proxy(http_proxy)
+            .build()
+            .expect("Build with proxies failed");
+    }
+
+    #[test]
+    #[cfg(feature = "socks")]
+    fn build_with_socks_proxy_succeeds() {
+        let proxy = Proxy::all("socks5://127.0.0.1:1080").expect("Invalid SOCKS proxy URL");
+
+        let _builder = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .with_proxy(proxy)
+            .build()
+            .expect("Build with a SOCKS proxy failed");
+    }
+
+    #[test]
+    fn build_with_existing_rsa_key_reuses_key() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+
+        let first_client = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_existing_rsa_key(rsa_key.clone())
+            .build()
+            .expect("Build with an existing RSA key failed");
+        let second_client = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_existing_rsa_key(rsa_key)
+            .build()
+            .expect("Build with an existing RSA key failed");
+
+        let first_pub_key = first_client
+            .rsa_key
+            .encoded_pub_key()
+            .expect("Failed to encode the public key");
+        let second_pub_key = second_client
+            .rsa_key
+            .encoded_pub_key()
+            .expect("Failed to encode the public key");
+        assert_eq!(first_pub_key, second_pub_key);
+    }
+
+    #[tokio::test]
+    async fn build_async_generates_key() {
+        let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let callback_phases = Arc::clone(&phases);
+
+        let client = ClientBuilder::default()
+            .with_keygen_progress(move |phase| {
+                callback_phases
+                    .lock()
+                    .expect("Keygen phases lock poisoned")
+                    .push(phase);
+            })
+            .build_async()
+            .await
+            .expect("Async build failed");
+
+        let encoded_pub_key = client
+            .rsa_key
+            .encoded_pub_key()
+            .expect("Failed to encode the public key");
+        assert!(!encoded_pub_key.is_empty());
+        assert_eq!(phases.lock().expect("Keygen phases lock poisoned").len(), 2);
+    }
+
+    #[test]
+    fn lazy_key_defers_key_generation() {
+        let client = ClientBuilder::default()
+            .lazy_key(true)
+            .with_keygen_progress(|_| panic!("Keygen progress reported for a deferred key"))
+            .build()
+            .expect("Build with a lazy key failed");
+
+        assert!(matches!(
+            client.rsa_key,
+            RsaKeyState::Deferred { key_size: 2048 }
+        ));
+    }
+
+    #[test]
+    fn build_reports_keygen_progress() {
+        let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let callback_phases = Arc::clone(&phases);
+
+        let _builder = ClientBuilder::new()
+            .with_server("oast.pro".into())
+            .with_rsa_key_size(2048)
+            .with_keygen_progress(move |phase| {
+                callback_phases
+                    .lock()
+                    .expect("Keygen phases lock poisoned")
+                    .push(phase);
+            })
+            .build()
+            .expect("Build with a keygen progress callback failed");
+
+        let phases = phases.lock().expect("Keygen phases lock poisoned");
+        assert_eq!(
+            *phases,
+            vec![
+                KeyGenPhase::Started { key_size: 2048 },
+                KeyGenPhase::Finished,
+            ]
+        );
+    }
+
     #[test]
     fn build_with_only_server_fails() {
         let _builder = ClientBuilder::new()
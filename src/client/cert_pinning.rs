@@ -0,0 +1,80 @@
+//! Certificate pinning for the rustls TLS backend, used when the client is
+//! built with [with_pinned_cert_sha256](crate::client::ClientBuilder::with_pinned_cert_sha256()).
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, CertificateError, ClientConfig, ServerName};
+use sha2::{Digest, Sha256};
+
+
+/// Verifies the server by comparing the SHA-256 hash of its leaf certificate
+/// with the pinned hash, instead of checking the certificate chain
+struct PinnedCertVerifier {
+    pinned_sha256: [u8; 32],
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let cert_sha256 = Sha256::digest(&end_entity.0);
+
+        if cert_sha256.as_slice() == self.pinned_sha256 {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+}
+
+/// Builds the rustls config that only accepts a server presenting the
+/// certificate with the pinned hash
+pub(crate) fn pinned_tls_config(pinned_sha256: [u8; 32]) -> ClientConfig {
+    ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier { pinned_sha256 }))
+        .with_no_client_auth()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::test_cert::test_cert_der;
+
+    fn verify_test_cert(pinned_sha256: [u8; 32]) -> Result<ServerCertVerified, rustls::Error> {
+        let cert_der = test_cert_der();
+        let server_name = ServerName::try_from("oast.pro").expect("Invalid server name");
+
+        PinnedCertVerifier { pinned_sha256 }.verify_server_cert(
+            &Certificate(cert_der),
+            &[],
+            &server_name,
+            &mut std::iter::empty(),
+            &[],
+            SystemTime::now(),
+        )
+    }
+
+    #[test]
+    fn pinned_cert_is_accepted() {
+        let pinned_sha256 = Sha256::digest(test_cert_der()).into();
+
+        assert!(verify_test_cert(pinned_sha256).is_ok());
+    }
+
+    #[test]
+    fn other_cert_is_rejected() {
+        assert!(verify_test_cert([0; 32]).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Generation of the correlation data (subdomain and correlation ID) that a
+//! client registers with an Interactsh server.
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use rand::distributions::{Alphanumeric, DistString};
+use rand::thread_rng;
+
+/// Length of the random subdomain generated for a client
+const SUB_DOMAIN_LENGTH: usize = 33;
+
+/// Length of the correlation ID (the prefix of the subdomain that the
+/// server uses to correlate interactions with a client)
+const CORRELATION_ID_LENGTH: usize = 20;
+
+
+/// The lengths of the subdomain and correlation ID expected by the
+/// Interactsh server
+///
+/// This is passed to the generator set with
+/// [with_correlation_generator](crate::client::ClientBuilder::with_correlation_generator()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrelationConfig {
+    subdomain_length: usize,
+    correlation_id_length: usize,
+}
+
+impl CorrelationConfig {
+    pub(crate) fn new(subdomain_length: usize, correlation_id_length: usize) -> Self {
+        Self {
+            subdomain_length,
+            correlation_id_length,
+        }
+    }
+
+    /// Gets the length of the subdomain
+    pub fn subdomain_length(&self) -> usize {
+        self.subdomain_length
+    }
+
+    /// Gets the length of the correlation ID (the prefix of the subdomain
+    /// that the server uses to correlate interactions with a client)
+    pub fn correlation_id_length(&self) -> usize {
+        self.correlation_id_length
+    }
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            subdomain_length: SUB_DOMAIN_LENGTH,
+            correlation_id_length: CORRELATION_ID_LENGTH,
+        }
+    }
+}
+
+/// The user-provided function that generates the subdomain and correlation
+/// ID of a client
+type CorrelationGeneratorFn = dyn Fn(&CorrelationConfig) -> (String, String) + Send + Sync;
+
+/// Wrapper for the user-provided function that generates the subdomain and
+/// correlation ID of a client
+#[derive(Clone)]
+pub(crate) struct CorrelationGenerator {
+    generator: Arc<CorrelationGeneratorFn>,
+}
+
+impl CorrelationGenerator {
+    pub(crate) fn new<F>(generator: F) -> Self
+    where
+        F: Fn(&CorrelationConfig) -> (String, String) + Send + Sync + 'static,
+    {
+        Self {
+            generator: Arc::new(generator),
+        }
+    }
+}
+
+impl std::fmt::Debug for CorrelationGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Correlation Generator>")
+    }
+}
+
+/// The subdomain and correlation ID pair used by a client session
+#[derive(Debug, Clone)]
+pub(crate) struct CorrelationData {
+    pub(crate) sub_domain: String,
+    pub(crate) correlation_id: String,
+}
+
+impl CorrelationData {
+    /// Generates a new random subdomain and the matching correlation ID with
+    /// the lengths in the config
+    pub(crate) fn generate_for(config: &CorrelationConfig) -> Self {
+        let sub_domain = Alphanumeric
+            .sample_string(&mut thread_rng(), config.subdomain_length)
+            .to_ascii_lowercase();
+        let mut correlation_id = sub_domain.clone();
+        correlation_id.truncate(config.correlation_id_length);
+
+        Self {
+            sub_domain,
+            correlation_id,
+        }
+    }
+
+    /// Generates the correlation data with the custom generator, or with
+    /// the default generator if there is none
+    pub(crate) fn generate_with(
+        generator: Option<&CorrelationGenerator>,
+        config: &CorrelationConfig,
+    ) -> Self {
+        match generator {
+            Some(generator) => {
+                let (sub_domain, correlation_id) = (generator.generator)(config);
+
+                Self {
+                    sub_domain,
+                    correlation_id,
+                }
+            }
+            None => Self::generate_for(config),
+        }
+    }
+
+    /// Checks whether the subdomain and correlation ID have the lengths in
+    /// the config
+    pub(crate) fn has_valid_lengths(&self, config: &CorrelationConfig) -> bool {
+        self.sub_domain.len() == config.subdomain_length
+            && self.correlation_id.len() == config.correlation_id_length
+    }
+}
+
+/// The interaction FQDN of a registered client, split into its subdomain
+/// and server domain
+///
+/// The Display output is the full FQDN (see [fqdn](InteractionFqdn::fqdn())).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InteractionFqdn {
+    subdomain: String,
+    server: String,
+}
+
+impl InteractionFqdn {
+    pub(crate) fn new(subdomain: &str, server: &str) -> Self {
+        Self {
+            subdomain: subdomain.to_owned(),
+            server: server.to_owned(),
+        }
+    }
+
+    /// Gets the subdomain (the part of the FQDN before the server domain)
+    pub fn subdomain(&self) -> &str {
+        &self.subdomain
+    }
+
+    /// Gets the domain of the Interactsh server
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Gets the full interaction FQDN
+    pub fn fqdn(&self) -> String {
+        format!("{}.{}", self.subdomain, self.server)
+    }
+
+    /// Builds a URL pointing to the interaction FQDN with the provided
+    /// scheme and path, such as `http://<fqdn>/some/path`.
+    ///
+    /// A leading slash on the path is optional.
+    pub fn url(&self, scheme: &str, path: &str) -> String {
+        format!(
+            "{}://{}/{}",
+            scheme,
+            self.fqdn(),
+            path.trim_start_matches('/')
+        )
+    }
+}
+
+impl Display for InteractionFqdn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.subdomain, self.server)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_correlation_id_is_prefix_of_sub_domain() {
+        let correlation_data = CorrelationData::generate_for(&CorrelationConfig::default());
+
+        assert_eq!(correlation_data.sub_domain.len(), SUB_DOMAIN_LENGTH);
+        assert_eq!(correlation_data.correlation_id.len(), CORRELATION_ID_LENGTH);
+        assert!(correlation_data
+            .sub_domain
+            .starts_with(correlation_data.correlation_id.as_str()));
+    }
+
+    #[test]
+    fn custom_generator_is_used_and_validated() {
+        let generator = CorrelationGenerator::new(|config| {
+            let sub_domain = format!("{:x<1$}", "tag", config.subdomain_length());
+            let correlation_id = sub_domain[..config.correlation_id_length()].to_owned();
+
+            (sub_domain, correlation_id)
+        });
+        let short_generator = CorrelationGenerator::new(|_| ("short".into(), "sh".into()));
+
+        let config = CorrelationConfig::default();
+
+        let correlation_data = CorrelationData::generate_with(Some(&generator), &config);
+
+        assert!(correlation_data.sub_domain.starts_with("tagxxx"));
+        assert!(correlation_data.has_valid_lengths(&config));
+        let short_correlation_data =
+            CorrelationData::generate_with(Some(&short_generator), &config);
+        assert!(!short_correlation_data.has_valid_lengths(&config));
+        assert!(CorrelationData::generate_with(None, &config).has_valid_lengths(&config));
+    }
+
+    #[test]
+    fn interaction_fqdn_builds_fqdn_and_url() {
+        let interaction_fqdn = InteractionFqdn::new("abc123", "oast.pro");
+
+        assert_eq!(interaction_fqdn.subdomain(), "abc123");
+        assert_eq!(interaction_fqdn.server(), "oast.pro");
+        assert_eq!(interaction_fqdn.fqdn(), "abc123.oast.pro");
+        assert_eq!(interaction_fqdn.to_string(), interaction_fqdn.fqdn());
+        assert_eq!(
+            interaction_fqdn.url("https", "/some/path"),
+            "https://abc123.oast.pro/some/path"
+        );
+        assert_eq!(interaction_fqdn.url("http", ""), "http://abc123.oast.pro/");
+    }
+
+    #[test]
+    fn generated_correlation_data_uses_config_lengths() {
+        let config = CorrelationConfig::new(16, 8);
+
+        let correlation_data = CorrelationData::generate_for(&config);
+
+        assert_eq!(correlation_data.sub_domain.len(), 16);
+        assert_eq!(correlation_data.correlation_id.len(), 8);
+        assert!(correlation_data.has_valid_lengths(&config));
+        assert!(!correlation_data.has_valid_lengths(&CorrelationConfig::default()));
+    }
+
+    #[test]
+    fn generated_correlation_data_is_lowercase() {
+        let correlation_data = CorrelationData::generate_for(&CorrelationConfig::default());
+
+        assert_eq!(
+            correlation_data.sub_domain,
+            correlation_data.sub_domain.to_ascii_lowercase()
+        );
+    }
+}
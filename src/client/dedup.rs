@@ -0,0 +1,225 @@
+//! Defines the [DedupPoller], which filters out logs that were already
+//! returned by an earlier poll.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, PoisonError};
+
+use super::errors::ClientPollError;
+use super::registered::RegisteredClient;
+use crate::interaction_log::LogEntry;
+
+
+/// Wraps a [RegisteredClient] and filters out logs that were already
+/// returned by an earlier poll.
+///
+/// The public Interactsh servers sometimes return the same interaction in
+/// consecutive polls. Parsed logs are identified by their protocol and unique
+/// ID; logs without a unique ID (raw logs and some protocols) are identified by
+/// a hash of the whole log.
+///
+/// Deduplication is per instance of this wrapper: logs seen by one
+/// `DedupPoller` (or by polling the inner client directly) are not known to
+/// another. Only the most recent `capacity` logs are remembered, which bounds
+/// memory use for long-running sessions.
+#[derive(Debug)]
+pub struct DedupPoller {
+    client: RegisteredClient,
+    seen_logs: Mutex<SeenLogs>,
+}
+
+impl DedupPoller {
+    /// Wraps the client, remembering up to `capacity` logs.
+    ///
+    /// If the capacity is 0, no logs are remembered and nothing is filtered.
+    pub fn new(client: RegisteredClient, capacity: usize) -> Self {
+        Self {
+            client,
+            seen_logs: Mutex::new(SeenLogs::new(capacity)),
+        }
+    }
+
+    /// Polls the Interactsh server for any new logs, filtering out the logs
+    /// returned by an earlier poll.
+    ///
+    /// Returns None if there are no new logs, including when every log
+    /// returned by the server was a duplicate.
+    pub async fn poll(&self) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
+        let logs = match self.client.poll().await? {
+            Some(logs) => logs,
+            None => return Ok(None),
+        };
+
+        let new_logs = self
+            .seen_logs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter_new(logs);
+
+        if new_logs.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(new_logs))
+        }
+    }
+
+    /// Gets a reference to the wrapped client
+    pub fn client(&self) -> &RegisteredClient {
+        &self.client
+    }
+
+    /// Unwraps this poller, returning the wrapped client
+    pub fn into_inner(self) -> RegisteredClient {
+        self.client
+    }
+}
+
+
+/// Bounded set of the keys of previously seen logs, evicting the oldest
+/// keys once full
+#[derive(Debug)]
+struct SeenLogs {
+    keys: HashSet<u64>,
+    key_order: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl SeenLogs {
+    fn new(capacity: usize) -> Self {
+        Self {
+            keys: HashSet::with_capacity(capacity),
+            key_order: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Removes the logs that were already seen (including duplicates within
+    /// the provided logs) and remembers the rest
+    fn filter_new(&mut self, logs: Vec<LogEntry>) -> Vec<LogEntry> {
+        if self.capacity == 0 {
+            return logs;
+        }
+
+        logs.into_iter()
+            .filter(|log_entry| self.insert(dedup_key(log_entry)))
+            .collect()
+    }
+
+    /// Remembers the key, returning false if it was already seen
+    fn insert(&mut self, key: u64) -> bool {
+        if !self.keys.insert(key) {
+            return false;
+        }
+
+        if self.key_order.len() == self.capacity {
+            if let Some(oldest_key) = self.key_order.pop_front() {
+                self.keys.remove(&oldest_key);
+            }
+        }
+        self.key_order.push_back(key);
+
+        true
+    }
+}
+
+/// Gets the key that identifies the interaction of a log
+fn dedup_key(log_entry: &LogEntry) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    match log_entry {
+        LogEntry::ParsedLog(parsed_log) => match parsed_log.unique_id() {
+            Some(unique_id) => (parsed_log.protocol(), unique_id).hash(&mut hasher),
+            None => serde_json::to_string(parsed_log)
+                .unwrap_or_else(|_| format!("{:?}", parsed_log))
+                .hash(&mut hasher),
+        },
+        LogEntry::RawLog(raw_log) => raw_log.as_bytes().hash(&mut hasher),
+    }
+
+    hasher.finish()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn http_log(unique_id: &str, raw_request: &str) -> LogEntry {
+        let json_log = json!({
+            "protocol": "http",
+            "unique-id": unique_id,
+            "full-id": unique_id,
+            "raw-request": raw_request,
+            "raw-response": "",
+            "remote-address": "10.0.0.1",
+            "timestamp": "2023-01-01T00:00:00Z"
+        });
+
+        LogEntry::try_parse_log(&json_log.to_string())
+    }
+
+    #[test]
+    fn logs_with_seen_unique_id_are_filtered() {
+        let mut seen_logs = SeenLogs::new(10);
+
+        let first_poll = seen_logs.filter_new(vec![http_log("id1", "a"), http_log("id2", "b")]);
+        let second_poll = seen_logs.filter_new(vec![http_log("id1", "c"), http_log("id3", "d")]);
+
+        assert_eq!(first_poll.len(), 2);
+        assert_eq!(second_poll.len(), 1);
+        assert!(second_poll[0].same_interaction(&http_log("id3", "d")));
+    }
+
+    #[test]
+    fn logs_without_unique_id_are_filtered_by_hash() {
+        let mut seen_logs = SeenLogs::new(10);
+        let smb_log = LogEntry::try_parse_log(
+            &json!({
+                "protocol": "smb",
+                "raw-request": "request",
+                "timestamp": "2023-01-01T00:00:00Z"
+            })
+            .to_string(),
+        );
+
+        let first_poll = seen_logs.filter_new(vec![
+            smb_log.clone(),
+            LogEntry::return_raw_log("raw log"),
+            LogEntry::return_raw_log("raw log"),
+        ]);
+        let second_poll = seen_logs.filter_new(vec![
+            smb_log,
+            LogEntry::return_raw_log("raw log"),
+            LogEntry::return_raw_log("other raw log"),
+        ]);
+
+        assert_eq!(first_poll.len(), 2);
+        assert_eq!(second_poll.len(), 1);
+    }
+
+    #[test]
+    fn oldest_logs_are_forgotten_once_full() {
+        let mut seen_logs = SeenLogs::new(2);
+
+        seen_logs.filter_new(vec![http_log("id1", ""), http_log("id2", "")]);
+        seen_logs.filter_new(vec![http_log("id3", "")]);
+        let new_logs = seen_logs.filter_new(vec![http_log("id1", ""), http_log("id3", "")]);
+
+        assert_eq!(new_logs.len(), 1);
+        assert!(new_logs[0].same_interaction(&http_log("id1", "")));
+        assert_eq!(seen_logs.keys.len(), 2);
+    }
+
+    #[test]
+    fn zero_capacity_filters_nothing() {
+        let mut seen_logs = SeenLogs::new(0);
+
+        seen_logs.filter_new(vec![http_log("id1", "")]);
+        let new_logs = seen_logs.filter_new(vec![http_log("id1", ""), http_log("id1", "")]);
+
+        assert_eq!(new_logs.len(), 2);
+    }
+}
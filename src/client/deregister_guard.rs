@@ -0,0 +1,231 @@
+//! Defines the guard that deregisters a
+//! [RegisteredClient](crate::client::RegisteredClient) when the last clone
+//! of the client is dropped.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use reqwest::header::{HeaderName, HeaderValue};
+use secrecy::{ExposeSecret, Secret};
+
+use super::builder::AuthToken;
+use super::http_utils::{self, DeregisterData, HttpRequest, RequestCustomizer, RequestOptions};
+use super::registered::RegisteredClient;
+
+/// Name of the thread that sends the deregister request
+const DEREGISTER_THREAD_NAME: &str = "interactsh-deregister";
+
+
+/// Sends a best-effort deregister request when dropped, unless disarmed
+///
+/// The guard is shared between clones of a client, so the request is only
+/// sent once the last clone is dropped.
+pub(crate) struct DeregisterGuard {
+    reqwest_client: reqwest::Client,
+    deregister_url: String,
+    auth_token: Option<AuthToken>,
+    user_agent: Option<String>,
+    custom_headers: Vec<(HeaderName, HeaderValue)>,
+    request_customizer: Option<RequestCustomizer>,
+    correlation_id: String,
+    secret_key: Secret<String>,
+    armed: AtomicBool,
+}
+
+impl DeregisterGuard {
+    pub(crate) fn new(client: &RegisteredClient) -> Self {
+        Self {
+            reqwest_client: client.reqwest_client.clone(),
+            deregister_url: http_utils::endpoint_url(
+                &client.server_url,
+                &client.endpoint_paths.deregister,
+                client.trailing_slash,
+            ),
+            auth_token: client.auth_token.clone(),
+            user_agent: client.user_agents.next_user_agent().map(str::to_owned),
+            custom_headers: client.custom_headers.clone(),
+            request_customizer: client.request_customizer.clone(),
+            correlation_id: client.correlation_id.clone(),
+            secret_key: client.secret_key.clone(),
+            armed: AtomicBool::new(true),
+        }
+    }
+
+    /// Stops the guard from deregistering the client when dropped
+    pub(crate) fn disarm(&self) {
+        self.armed.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for DeregisterGuard {
+    fn drop(&mut self) {
+        if !*self.armed.get_mut() {
+            return;
+        }
+
+        let reqwest_client = self.reqwest_client.clone();
+        let deregister_url = std::mem::take(&mut self.deregister_url);
+        let auth_token = self.auth_token.take();
+        let user_agent = self.user_agent.take();
+        let custom_headers = std::mem::take(&mut self.custom_headers);
+        let request_customizer = self.request_customizer.take();
+        let post_data = DeregisterData {
+            correlation_id: std::mem::take(&mut self.correlation_id),
+            secret_key: self.secret_key.expose_secret().clone(),
+        };
+
+        // The request is sent on a detached thread so that dropping the client
+        // never blocks or panics, whether or not an async runtime is running.
+        // If the thread can't be spawned, the client is left registered.
+        let _spawn_result = thread::Builder::new()
+            .name(DEREGISTER_THREAD_NAME.into())
+            .spawn(move || {
+                let request_info = HttpRequest::Post {
+                    url: deregister_url,
+                    post_data,
+                };
+
+                let request_options = RequestOptions {
+                    auth_token: auth_token.as_ref(),
+                    user_agent: user_agent.as_deref(),
+                    custom_headers: &custom_headers,
+                    timeout: None,
+                    request_customizer: request_customizer.as_ref(),
+                };
+
+                let _deregister_result = block_on(http_utils::make_http_request(
+                    &reqwest_client,
+                    request_options,
+                    request_info,
+                ));
+            });
+    }
+}
+
+impl std::fmt::Debug for DeregisterGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Deregister Guard>")
+    }
+}
+
+
+/// Waker that unparks the thread blocked on a future
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the future to completion on the current thread
+///
+/// The HTTP request future is wrapped with async-compat, which provides the
+/// runtime that reqwest needs, so a simple park/unpark loop is enough here.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::client::mock_server::{MockResponse, MockServer};
+
+    /// How long to wait for the detached thread to send the deregister request
+    const DEREGISTER_WAIT: Duration = Duration::from_secs(5);
+
+    fn build_test_client(server: &MockServer) -> RegisteredClient {
+        server
+            .client_builder()
+            .deregister_on_drop(true)
+            .build()
+            .expect("Build with deregister_on_drop failed")
+            .into_registered()
+            .expect("Failed to convert the client")
+    }
+
+    #[test]
+    fn block_on_returns_future_output() {
+        assert_eq!(block_on(async { 42 }), 42);
+    }
+
+    #[test]
+    fn deregister_on_drop_adds_a_shared_guard() {
+        let server = MockServer::start();
+        let client = build_test_client(&server);
+        let client_clone = client.clone();
+
+        let guard = client
+            .deregister_guard
+            .as_ref()
+            .expect("No deregister guard was added");
+        assert_eq!(Arc::strong_count(guard), 2);
+
+        drop(client_clone);
+        assert_eq!(Arc::strong_count(guard), 1);
+    }
+
+    #[test]
+    fn dropping_an_armed_guard_deregisters() {
+        let server = MockServer::start();
+        server.queue_response("/deregister", MockResponse::ok(""));
+        let client = build_test_client(&server);
+        let correlation_id = client.correlation_id.clone();
+
+        drop(client);
+
+        let wait_start = Instant::now();
+        while server.requests_to("/deregister").is_empty() && wait_start.elapsed() < DEREGISTER_WAIT
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let deregister_requests = server.requests_to("/deregister");
+        assert_eq!(deregister_requests.len(), 1);
+        assert_eq!(
+            deregister_requests[0].json_body()["correlation-id"],
+            correlation_id
+        );
+    }
+
+    #[test]
+    fn dropping_a_disarmed_guard_does_not_deregister() {
+        let server = MockServer::start();
+        let client = build_test_client(&server);
+        client
+            .deregister_guard
+            .as_ref()
+            .expect("No deregister guard was added")
+            .disarm();
+
+        drop(client);
+
+        assert!(server.requests_to("/deregister").is_empty());
+    }
+
+    #[test]
+    fn exporting_the_session_disarms_the_guard() {
+        let server = MockServer::start();
+        let client = build_test_client(&server);
+
+        client.export_session().expect("Session export failed");
+        drop(client);
+
+        assert!(server.requests_to("/deregister").is_empty());
+    }
+}
@@ -1,15 +1,21 @@
 //! Error types for the [Client](crate::client) module.
 
+use std::time::Duration;
+
 use snafu::prelude::*;
 use snafu::Backtrace;
 
 use super::http_utils::Client;
+use super::registered::RegisteredClient;
+use super::unregistered::UnregisteredClient;
 use crate::crypto::errors::CryptoError;
+use crate::interaction_log::LogEntry;
 
 
 /// Inner error type for [ClientRegistrationError]
 #[derive(Debug, Snafu)]
 #[snafu(module, context(suffix(false)), visibility(pub(crate)))]
+#[non_exhaustive]
 pub enum RegistrationError {
     #[snafu(display("Failed to send the request to the server"))]
     RequestSendFailure { source: reqwest::Error },
@@ -17,6 +23,30 @@ pub enum RegistrationError {
     #[snafu(display("Server returned an Unauthorized status code"))]
     Unauthorized { backtrace: Backtrace },
 
+    #[snafu(display("Correlation ID is already registered with the server"))]
+    CorrelationIdExists { backtrace: Backtrace },
+
+    #[snafu(display("Failed to serialize the register request body"))]
+    PayloadSerializeFailed { source: serde_json::Error },
+
+    #[snafu(display(
+        "Generated subdomain ({subdomain_len} chars) or correlation ID ({correlation_id_len} chars) has an invalid length"
+    ))]
+    InvalidCorrelationData {
+        subdomain_len: usize,
+        correlation_id_len: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Server did not report its correlation settings in the register response"))]
+    CorrelationDiscoveryUnsupported { backtrace: Backtrace },
+
+    #[snafu(display("Failed to generate the deferred RSA private key"))]
+    RsaGen { source: CryptoError },
+
+    #[snafu(display("The deferred RSA private key was not generated"))]
+    RsaKeyNotGenerated { backtrace: Backtrace },
+
     #[snafu(display(
         "Failed to register or deregister with the server - {status_code}: {server_msg}"
     ))]
@@ -27,6 +57,31 @@ pub enum RegistrationError {
     },
 }
 
+impl RegistrationError {
+    /// Checks whether the error is likely to go away if the request is retried
+    ///
+    /// This is true for failures to send the request and for 5xx status codes
+    /// returned by the server.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RegistrationError::RequestSendFailure { .. } => true,
+            RegistrationError::RegistrationFailure { status_code, .. } => {
+                is_server_error_status(*status_code)
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets the status code returned by the server, if there is one
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            RegistrationError::Unauthorized { .. } => Some(401),
+            RegistrationError::RegistrationFailure { status_code, .. } => Some(*status_code),
+            _ => None,
+        }
+    }
+}
+
 /// Error returned during client registration or deregistration
 ///
 /// This is a wrapper error. See [RegistrationError] for the inner error types.
@@ -43,10 +98,37 @@ pub struct ClientRegistrationError<C: Client + Clone> {
     pub client: C,
 }
 
+impl<C: Client + Clone> ClientRegistrationError<C> {
+    /// Checks whether the error is likely to go away if the request is retried
+    ///
+    /// See [RegistrationError::is_transient()].
+    pub fn is_transient(&self) -> bool {
+        self.error.is_transient()
+    }
+
+    /// Gets the status code returned by the server, if there is one
+    pub fn status_code(&self) -> Option<u16> {
+        self.error.status_code()
+    }
+}
+
+impl ClientRegistrationError<UnregisteredClient> {
+    /// Tries to register the client returned in this error again.
+    ///
+    /// If the registration fails again, the new error contains the client
+    /// for another try.
+    pub async fn retry(
+        self,
+    ) -> Result<RegisteredClient, ClientRegistrationError<UnregisteredClient>> {
+        self.client.register().await
+    }
+}
+
 
 /// Errors returned by the [ClientBuilder](crate::client::ClientBuilder)
 #[derive(Debug, Snafu)]
 #[snafu(module, context(suffix(false)), visibility(pub(crate)))]
+#[non_exhaustive]
 pub enum ClientBuildError {
     #[snafu(display("Builder failed to generate the RSA private key"))]
     RsaGen { source: CryptoError },
@@ -65,12 +147,47 @@ pub enum ClientBuildError {
 
     #[snafu(display("Failed to build the reqwest client"))]
     ReqwestBuildFailed { source: reqwest::Error },
+
+    #[snafu(display("Failed to import the RSA private key from the session"))]
+    SessionKeyImport { source: CryptoError },
+
+    #[snafu(display(
+        "Generated subdomain ({subdomain_len} chars) or correlation ID ({correlation_id_len} chars) has an invalid length"
+    ))]
+    InvalidCorrelationData {
+        subdomain_len: usize,
+        correlation_id_len: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Failed to parse a PEM encoded root certificate"))]
+    RootCertificateParse { source: reqwest::Error },
+
+    #[snafu(display("Failed to build the runtime of the blocking client"))]
+    RuntimeBuildFailed { source: std::io::Error },
+}
+
+
+/// Errors returned by [quick_client](crate::client::quick_client())
+#[derive(Debug, Snafu)]
+#[snafu(module, context(suffix(false)), visibility(pub(crate)))]
+#[non_exhaustive]
+pub enum QuickClientError {
+    #[snafu(display("Failed to build the client"))]
+    Build { source: ClientBuildError },
+
+    #[snafu(display("Failed to register the client"))]
+    Register {
+        #[snafu(source(from(ClientRegistrationError<UnregisteredClient>, Box::new)))]
+        source: Box<ClientRegistrationError<UnregisteredClient>>,
+    },
 }
 
 
 /// Errors returned by the [RegisteredClient](crate::client::RegisteredClient) when polling the server
 #[derive(Debug, Snafu)]
 #[snafu(module, context(suffix(false)), visibility(pub(crate)))]
+#[non_exhaustive]
 pub enum ClientPollError {
     #[snafu(display("Client failed to poll the Interactsh server"))]
     PollFailure { source: reqwest::Error },
@@ -82,8 +199,17 @@ pub enum ClientPollError {
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Server response is not valid JSON"))]
-    ResponseJsonParseFailed { source: reqwest::Error },
+    #[snafu(display("Interactsh server rate limited the client, retry after {retry_after:?}"))]
+    RateLimited { retry_after: Duration },
+
+    #[snafu(display("Failed to read the server response body"))]
+    ResponseBodyReadFailed { source: reqwest::Error },
+
+    #[snafu(display("Server response is not valid JSON (response body: {body_snippet})"))]
+    ResponseJsonParseFailed {
+        source: serde_json::Error,
+        body_snippet: String,
+    },
 
     #[snafu(display("Failed to decrypt the AES key"))]
     AesKeyDecryptFailed { source: CryptoError },
@@ -93,4 +219,154 @@ pub enum ClientPollError {
 
     #[snafu(display("Base64 decoding failed"))]
     Base64DecodeFailed { source: base64::DecodeError },
+
+    #[snafu(display("A log sink failed to consume a polled log"))]
+    SinkFailure {
+        source: SinkError,
+        /// The logs returned by the poll, which the server no longer holds
+        logs: Vec<LogEntry>,
+    },
+
+    #[snafu(display("Client was deregistered from the Interactsh server"))]
+    NotRegistered { backtrace: Backtrace },
+}
+
+impl ClientPollError {
+    /// Checks whether the error is likely to go away if the poll is retried
+    ///
+    /// This is true for transport failures while sending the request or reading
+    /// the response, for rate limiting, and for 5xx status codes returned by the
+    /// server. Decryption failures and other 4xx status codes are not transient.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ClientPollError::PollFailure { .. }
+            | ClientPollError::RateLimited { .. }
+            | ClientPollError::ResponseBodyReadFailed { .. } => true,
+            ClientPollError::PollErrorStatus { status_code, .. } => {
+                is_server_error_status(*status_code)
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets the status code returned by the server, if there is one
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            ClientPollError::PollErrorStatus { status_code, .. } => Some(*status_code),
+            ClientPollError::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+}
+
+/// Checks whether the status code is a 5xx server error
+fn is_server_error_status(status_code: u16) -> bool {
+    (500..600).contains(&status_code)
+}
+
+
+/// Error returned by a [LogSink](crate::client::LogSink) when it fails to
+/// consume a log entry
+#[derive(Debug, Snafu)]
+#[snafu(context(false), display("Log sink failed to consume the log entry"))]
+pub struct SinkError {
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl SinkError {
+    /// Creates a new [SinkError] wrapping the provided error
+    pub fn new<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self {
+            source: error.into(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClientBuilder;
+
+    fn poll_error_status(status_code: u16) -> ClientPollError {
+        client_poll_error::PollErrorStatus {
+            server_msg: "error",
+            status_code,
+        }
+        .build()
+    }
+
+    #[test]
+    fn poll_errors_are_transient_for_transport_failures_and_5xx() {
+        let source = reqwest::Client::new()
+            .get("not a url")
+            .build()
+            .expect_err("Invalid URL built successfully");
+
+        assert!(ClientPollError::PollFailure { source }.is_transient());
+        assert!(poll_error_status(503).is_transient());
+        assert!(!poll_error_status(400).is_transient());
+        assert!(!ClientPollError::DataDecryptFailed {
+            source: CryptoError::AesKeySize { key_len: 1 },
+        }
+        .is_transient());
+    }
+
+    #[test]
+    fn poll_error_status_code_is_returned_when_present() {
+        let decode_error = ClientPollError::Base64DecodeFailed {
+            source: base64::DecodeError::InvalidLength,
+        };
+
+        let rate_limited = client_poll_error::RateLimited {
+            retry_after: Duration::from_secs(1),
+        }
+        .build();
+
+        assert_eq!(poll_error_status(502).status_code(), Some(502));
+        assert_eq!(rate_limited.status_code(), Some(429));
+        assert!(rate_limited.is_transient());
+        assert_eq!(decode_error.status_code(), None);
+    }
+
+    #[test]
+    fn registration_errors_report_status_code_and_transience() {
+        let unauthorized = registration_error::Unauthorized.build();
+        let server_failure = registration_error::RegistrationFailure {
+            server_msg: "error",
+            status_code: 500u16,
+        }
+        .build();
+
+        assert_eq!(unauthorized.status_code(), Some(401));
+        assert!(!unauthorized.is_transient());
+        assert_eq!(server_failure.status_code(), Some(500));
+        assert!(server_failure.is_transient());
+    }
+
+    #[tokio::test]
+    async fn failed_registration_is_retried_with_the_same_client() {
+        let client = ClientBuilder::new()
+            .with_server("127.0.0.1".into())
+            .with_server_port(9)
+            .use_tls(false)
+            .with_rsa_key_size(2048)
+            .build()
+            .expect("Build with a closed port failed");
+        let predicted_fqdn = client.predicted_fqdn();
+
+        let registration_error = match client.register().await {
+            Ok(_) => panic!("Registration with a closed port succeeded"),
+            Err(registration_error) => registration_error,
+        };
+        let retry_error = match registration_error.retry().await {
+            Ok(_) => panic!("Retried registration with a closed port succeeded"),
+            Err(retry_error) => retry_error,
+        };
+
+        assert_eq!(retry_error.client.predicted_fqdn(), predicted_fqdn);
+    }
 }
@@ -0,0 +1,104 @@
+//! Defines the bounded history of polled logs that a
+//! [RegisteredClient](crate::client::RegisteredClient) can retain.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::interaction_log::LogEntry;
+
+
+/// Ring buffer holding the most recently polled logs
+///
+/// The history is shared between clones of a client. If the max size
+/// is 0, no logs are retained.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LogHistory {
+    logs: Arc<Mutex<VecDeque<LogEntry>>>,
+    max_history: usize,
+}
+
+impl LogHistory {
+    pub(crate) fn new(max_history: usize) -> Self {
+        Self {
+            logs: Arc::new(Mutex::new(VecDeque::with_capacity(max_history))),
+            max_history,
+        }
+    }
+
+    /// Adds the logs to the history, evicting the oldest logs once the
+    /// history is full
+    pub(crate) fn record(&self, new_logs: &[LogEntry]) {
+        if self.max_history == 0 {
+            return;
+        }
+
+        let mut logs = self.logs.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Only the most recent logs can fit in the history
+        let skip_count = new_logs.len().saturating_sub(self.max_history);
+        for log_entry in new_logs.iter().skip(skip_count) {
+            if logs.len() == self.max_history {
+                logs.pop_front();
+            }
+            logs.push_back(log_entry.clone());
+        }
+    }
+
+    /// Gets a copy of the logs in the history, from oldest to newest
+    pub(crate) fn logs(&self) -> Vec<LogEntry> {
+        let logs = self.logs.lock().unwrap_or_else(PoisonError::into_inner);
+
+        logs.iter().cloned().collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_logs(log_strings: &[&str]) -> Vec<LogEntry> {
+        log_strings
+            .iter()
+            .map(|log| LogEntry::return_raw_log(log))
+            .collect()
+    }
+
+    fn raw_log_strings(logs: &[LogEntry]) -> Vec<String> {
+        logs.iter()
+            .map(|log_entry| match log_entry {
+                LogEntry::RawLog(raw_log) => raw_log.log_entry.clone(),
+                LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn history_evicts_oldest_logs_once_full() {
+        let history = LogHistory::new(3);
+
+        history.record(&raw_logs(&["1", "2"]));
+        history.record(&raw_logs(&["3", "4"]));
+        history.record(&raw_logs(&["5"]));
+
+        assert_eq!(raw_log_strings(&history.logs()), vec!["3", "4", "5"]);
+    }
+
+    #[test]
+    fn history_keeps_most_recent_logs_from_large_poll() {
+        let history = LogHistory::new(2);
+
+        history.record(&raw_logs(&["1", "2", "3", "4"]));
+
+        assert_eq!(raw_log_strings(&history.logs()), vec!["3", "4"]);
+    }
+
+    #[test]
+    fn empty_history_retains_nothing() {
+        let history = LogHistory::default();
+
+        history.record(&raw_logs(&["1"]));
+
+        assert!(history.logs().is_empty());
+    }
+}
@@ -1,15 +1,46 @@
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(feature = "async-compat")]
 use async_compat::Compat;
-use reqwest::{RequestBuilder, Response, StatusCode};
-use secrecy::{ExposeSecret, Secret};
+use reqwest::header::{HeaderName, HeaderValue, USER_AGENT};
+use reqwest::{Request, RequestBuilder, Response, StatusCode};
+use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use snafu::ResultExt;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::{OffsetDateTime, PrimitiveDateTime};
 
+use super::builder::AuthToken;
+use super::correlation::CorrelationConfig;
 use super::errors::{registration_error, RegistrationError};
 
+/// Message returned by the Interactsh server when a client tries to register
+/// a correlation ID that is already in use
+const CORRELATION_ID_EXISTS_MSG: &str = "correlation-id provided already exists";
+
+/// Maximum number of characters of a response body included in error messages
+const BODY_SNIPPET_MAX_CHARS: usize = 256;
+
+/// Format of the HTTP dates accepted in a Retry-After header
+const HTTP_DATE_FORMAT: &[FormatItem<'static>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+/// Time to wait after the server rate limits the client if it does not send
+/// a valid Retry-After header
+pub(crate) const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Default port of an Interactsh server
+pub(crate) const DEFAULT_SERVER_PORT: u16 = 443;
+
+/// Default port of an Interactsh server that does not use TLS
+pub(crate) const DEFAULT_PLAINTEXT_SERVER_PORT: u16 = 80;
+
 
 // Serde objects
 
@@ -24,6 +55,84 @@ pub(crate) struct PollResponse {
 }
 
 
+/// Serde struct used to deserialize the json body
+/// of a successful register or deregister response
+///
+/// Some servers assign the subdomain themselves or report their correlation
+/// settings in the registration response. All fields are optional, and a
+/// body that can't be parsed (such as the plain text body returned by the
+/// public servers) is treated as an empty response.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegisterResponse {
+    pub(crate) subdomain: Option<String>,
+    pub(crate) fqdn: Option<String>,
+
+    #[serde(rename(deserialize = "correlation-id-length"))]
+    pub(crate) correlation_id_length: Option<usize>,
+
+    #[serde(rename(deserialize = "correlation-id-nonce-length"))]
+    pub(crate) correlation_id_nonce_length: Option<usize>,
+}
+
+impl RegisterResponse {
+    /// Gets the subdomain returned by the server, if there is one
+    pub fn subdomain(&self) -> Option<&str> {
+        self.subdomain.as_deref()
+    }
+
+    /// Gets the interaction FQDN returned by the server, if there is one
+    pub fn fqdn(&self) -> Option<&str> {
+        self.fqdn.as_deref()
+    }
+
+    /// Gets the length of the correlation IDs used by the server, if it
+    /// reported it
+    pub fn correlation_id_length(&self) -> Option<usize> {
+        self.correlation_id_length
+    }
+
+    /// Gets the length of the nonce that follows the correlation ID in the
+    /// subdomain, if the server reported it
+    pub fn correlation_id_nonce_length(&self) -> Option<usize> {
+        self.correlation_id_nonce_length
+    }
+
+    /// Gets the correlation settings of the server, if it reported both the
+    /// correlation ID and nonce lengths
+    pub fn correlation_config(&self) -> Option<CorrelationConfig> {
+        let correlation_id_length = self.correlation_id_length?;
+        let nonce_length = self.correlation_id_nonce_length?;
+
+        Some(CorrelationConfig::new(
+            correlation_id_length + nonce_length,
+            correlation_id_length,
+        ))
+    }
+
+    /// Leniently parses the response body, ignoring any unknown or malformed fields
+    fn parse(response_body: &str) -> Self {
+        serde_json::from_str(response_body).unwrap_or_default()
+    }
+
+    /// Gets the subdomain assigned by the server, if the server returned one.
+    ///
+    /// If only the FQDN was returned, the subdomain is taken from the FQDN
+    /// when it ends with the server domain.
+    pub(crate) fn assigned_sub_domain(&self, server: &str) -> Option<String> {
+        if let Some(subdomain) = self.subdomain.as_ref().filter(|s| !s.is_empty()) {
+            return Some(subdomain.clone());
+        }
+
+        let server_suffix = format!(".{}", server);
+        self.fqdn
+            .as_ref()
+            .and_then(|fqdn| fqdn.strip_suffix(server_suffix.as_str()))
+            .filter(|subdomain| !subdomain.is_empty())
+            .map(str::to_owned)
+    }
+}
+
+
 /// Serde struct used to serialize the body data
 /// for a deregister post request
 #[derive(Serialize)]
@@ -51,6 +160,66 @@ pub struct RegisterData {
 }
 
 
+/// Wrapper for the user-provided hook that can modify the serialized
+/// register request body before it is sent
+#[derive(Clone)]
+pub(crate) struct RegisterPayloadHook {
+    hook: Arc<dyn Fn(&mut serde_json::Value) + Send + Sync>,
+}
+
+impl RegisterPayloadHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    {
+        Self {
+            hook: Arc::new(hook),
+        }
+    }
+
+    /// Runs the hook on the serialized register request body
+    pub(crate) fn apply(&self, payload: &mut serde_json::Value) {
+        (self.hook)(payload);
+    }
+}
+
+impl std::fmt::Debug for RegisterPayloadHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Register Payload Hook>")
+    }
+}
+
+
+/// Wrapper for the user-provided function that can customize each request
+/// before it is sent
+#[derive(Clone)]
+pub struct RequestCustomizer {
+    customizer: Arc<dyn Fn(RequestBuilder) -> RequestBuilder + Send + Sync>,
+}
+
+impl RequestCustomizer {
+    pub(crate) fn new<F>(customizer: F) -> Self
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder + Send + Sync + 'static,
+    {
+        Self {
+            customizer: Arc::new(customizer),
+        }
+    }
+
+    /// Runs the customizer on the request builder
+    pub(crate) fn apply(&self, http_request: RequestBuilder) -> RequestBuilder {
+        (self.customizer)(http_request)
+    }
+}
+
+impl std::fmt::Debug for RequestCustomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Request Customizer>")
+    }
+}
+
+
 /// Unit struct used only as an unused generic placeholder
 #[derive(Serialize)]
 pub struct EmptyData;
@@ -65,28 +234,53 @@ pub trait Client {
 
     fn get_reqwest_client(&self) -> &reqwest::Client;
 
-    fn get_auth_token(&self) -> Option<&Secret<String>>;
+    fn get_auth_token(&self) -> Option<&AuthToken>;
+
+    fn get_user_agents(&self) -> &UserAgentRotation;
+
+    fn get_custom_headers(&self) -> &[(HeaderName, HeaderValue)];
+
+    fn get_request_customizer(&self) -> Option<&RequestCustomizer>;
 
     /// Sends a post request to register or deregister a [Client]
     async fn do_registration_request<P: Serialize + Send>(
         &self,
         post_data: P,
-    ) -> Result<(), RegistrationError> {
-        let reqwest_client = self.get_reqwest_client();
-        let register_url = self.get_registration_url();
-        let auth_token = self.get_auth_token();
+    ) -> Result<RegisterResponse, RegistrationError> {
+        self.do_registration_request_to(self.get_registration_url(), post_data)
+            .await
+    }
 
+    /// Sends a post request to register or deregister a [Client] to the
+    /// provided URL instead of the client's registration URL
+    async fn do_registration_request_to<P: Serialize + Send>(
+        &self,
+        register_url: String,
+        post_data: P,
+    ) -> Result<RegisterResponse, RegistrationError> {
+        let request_options = RequestOptions {
+            auth_token: self.get_auth_token(),
+            user_agent: self.get_user_agents().next_user_agent(),
+            custom_headers: self.get_custom_headers(),
+            timeout: None,
+            request_customizer: self.get_request_customizer(),
+        };
         let request_info = HttpRequest::Post {
             url: register_url,
             post_data,
         };
 
-        let register_response = make_http_request(reqwest_client, auth_token, request_info)
-            .await
-            .context(registration_error::RequestSendFailure)?;
+        let register_response =
+            make_http_request(self.get_reqwest_client(), request_options, request_info)
+                .await
+                .context(registration_error::RequestSendFailure)?;
 
         match register_response.status() {
-            StatusCode::OK => Ok(()),
+            StatusCode::OK => {
+                let response_body = register_response.text().await.unwrap_or_default();
+
+                Ok(RegisterResponse::parse(&response_body))
+            }
             StatusCode::UNAUTHORIZED => registration_error::Unauthorized.fail(),
             status => {
                 let server_msg = register_response
@@ -95,6 +289,10 @@ pub trait Client {
                     .unwrap_or_else(|_| "Unknown error".to_string());
                 let status_code = status.as_u16();
 
+                if is_correlation_collision(&server_msg) {
+                    return registration_error::CorrelationIdExists.fail();
+                }
+
                 let error = registration_error::RegistrationFailure {
                     server_msg,
                     status_code,
@@ -106,6 +304,122 @@ pub trait Client {
     }
 }
 
+/// Checks if the server message indicates that the correlation ID sent
+/// in a registration request is already in use
+fn is_correlation_collision(server_msg: &str) -> bool {
+    server_msg.contains(CORRELATION_ID_EXISTS_MSG)
+}
+
+/// Builds the base URL used for all requests to the server, omitting the
+/// port if it is the default port
+pub(crate) fn server_base_url(server: &str, port: u16, use_tls: bool) -> String {
+    let (scheme, default_port) = if use_tls {
+        ("https", DEFAULT_SERVER_PORT)
+    } else {
+        ("http", DEFAULT_PLAINTEXT_SERVER_PORT)
+    };
+
+    if port == default_port {
+        format!("{}://{}", scheme, server)
+    } else {
+        format!("{}://{}:{}", scheme, server, port)
+    }
+}
+
+/// The paths of the endpoints of an Interactsh server, without leading or
+/// trailing slashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EndpointPaths {
+    pub(crate) poll: String,
+    pub(crate) register: String,
+    pub(crate) deregister: String,
+}
+
+impl EndpointPaths {
+    /// Strips the leading and trailing slashes from an endpoint path
+    pub(crate) fn normalize(path: &str) -> String {
+        path.trim_matches('/').to_owned()
+    }
+}
+
+impl Default for EndpointPaths {
+    fn default() -> Self {
+        Self {
+            poll: "poll".into(),
+            register: "register".into(),
+            deregister: "deregister".into(),
+        }
+    }
+}
+
+/// Builds the URL of a server endpoint, appending a trailing slash to the
+/// path if needed
+pub(crate) fn endpoint_url(server_url: &str, path: &str, trailing_slash: bool) -> String {
+    if trailing_slash {
+        format!("{}/{}/", server_url, path)
+    } else {
+        format!("{}/{}", server_url, path)
+    }
+}
+
+/// Truncates a server response body for inclusion in an error message
+pub(crate) fn body_snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_MAX_CHARS) {
+        Some((truncate_index, _)) => format!("{}...", &body[..truncate_index]),
+        None => body.to_string(),
+    }
+}
+
+/// Parses the value of a Retry-After header, which is either a number of
+/// seconds or an HTTP date
+///
+/// Dates in the past result in a zero duration. Returns None if the value
+/// is in neither format.
+pub(crate) fn parse_retry_after(header_value: &str, now: OffsetDateTime) -> Option<Duration> {
+    let header_value = header_value.trim();
+    if let Ok(seconds) = header_value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let retry_at = PrimitiveDateTime::parse(header_value, HTTP_DATE_FORMAT)
+        .ok()?
+        .assume_utc();
+
+    Some((retry_at - now).try_into().unwrap_or_default())
+}
+
+/// List of User-Agent headers that the client cycles through, one per request
+///
+/// The position in the list is shared between clones, so the rotation
+/// continues when an [UnregisteredClient](super::unregistered::UnregisteredClient)
+/// is turned into a [RegisteredClient](super::registered::RegisteredClient).
+#[derive(Debug, Clone, Default)]
+pub struct UserAgentRotation {
+    user_agents: Arc<Vec<String>>,
+    next_index: Arc<AtomicUsize>,
+}
+
+impl UserAgentRotation {
+    pub(crate) fn new(user_agents: Vec<String>) -> Self {
+        Self {
+            user_agents: Arc::new(user_agents),
+            next_index: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Gets the User-Agent to use for the next request, or `None` if
+    /// no User-Agents were provided
+    pub(crate) fn next_user_agent(&self) -> Option<&str> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed) % self.user_agents.len();
+
+        Some(self.user_agents[index].as_str())
+    }
+}
+
 pub enum HttpRequest<P: Serialize + Send> {
     Get {
         url: String,
@@ -116,6 +430,10 @@ pub enum HttpRequest<P: Serialize + Send> {
         url: String,
         post_data: P,
     },
+    Head {
+        url: String,
+        phantom_data: PhantomData<P>,
+    },
 }
 
 impl HttpRequest<EmptyData> {
@@ -131,6 +449,12 @@ impl HttpRequest<EmptyData> {
             phantom_data,
         }
     }
+
+    pub fn new_head_request(url: String) -> HttpRequest<EmptyData> {
+        let phantom_data: PhantomData<EmptyData> = PhantomData;
+
+        Self::Head { url, phantom_data }
+    }
 }
 
 impl<P: Serialize + Send> HttpRequest<P> {
@@ -140,31 +464,426 @@ impl<P: Serialize + Send> HttpRequest<P> {
                 url, query_params, ..
             } => reqwest_client.get(url).query(query_params),
             HttpRequest::Post { url, post_data } => reqwest_client.post(url).json(&post_data),
+            HttpRequest::Head { url, .. } => reqwest_client.head(url),
         }
     }
 }
 
-pub async fn make_http_request<P: Serialize + Send>(
+/// The per-request options added to an [HttpRequest] when it is sent
+#[derive(Default)]
+pub struct RequestOptions<'a> {
+    pub auth_token: Option<&'a AuthToken>,
+    pub user_agent: Option<&'a str>,
+    pub custom_headers: &'a [(HeaderName, HeaderValue)],
+    pub timeout: Option<Duration>,
+    pub request_customizer: Option<&'a RequestCustomizer>,
+}
+
+/// Creates the request, adding the custom, auth and User-Agent headers and
+/// the per-request timeout if provided
+///
+/// The request customizer, if provided, is run on a request builder without
+/// the standard headers. Headers set by the customizer replace the standard
+/// headers of the same name, and the remaining standard headers are added
+/// after it runs.
+fn build_http_request<P: Serialize + Send>(
     reqwest_client: &reqwest::Client,
-    auth_token: Option<&Secret<String>>,
+    request_options: RequestOptions<'_>,
     request_info: HttpRequest<P>,
-) -> Result<Response, reqwest::Error> {
-    let mut http_request = request_info.create_request_builder(reqwest_client);
+) -> Result<Request, reqwest::Error> {
+    let RequestOptions {
+        auth_token,
+        user_agent,
+        custom_headers,
+        timeout,
+        request_customizer,
+    } = request_options;
+
+    let new_request_builder = || {
+        let http_request = request_info.create_request_builder(reqwest_client);
+
+        match timeout {
+            Some(timeout) => http_request.timeout(timeout),
+            None => http_request,
+        }
+    };
+
+    let mut http_request = new_request_builder();
+
+    for (header_name, header_value) in custom_headers {
+        http_request = http_request.header(header_name, header_value);
+    }
 
     http_request = match auth_token {
-        Some(token) => http_request.header("Authorization", token.expose_secret()),
+        Some(AuthToken::SimpleAuth(token)) => {
+            http_request.header("Authorization", token.expose_secret())
+        }
+        Some(AuthToken::BearerAuth(token)) => {
+            http_request.header("Authorization", format!("Bearer {}", token.expose_secret()))
+        }
         None => http_request,
     };
 
+    http_request = match user_agent {
+        Some(user_agent) => http_request.header(USER_AGENT, user_agent),
+        None => http_request,
+    };
+
+    let request_customizer = match request_customizer {
+        Some(request_customizer) => request_customizer,
+        None => return http_request.build(),
+    };
+
+    let standard_request = http_request.build()?;
+    let mut customized_request = request_customizer.apply(new_request_builder()).build()?;
+
+    let standard_headers = standard_request.headers();
+    let customized_headers = customized_request.headers_mut();
+    for header_name in standard_headers.keys() {
+        if customized_headers.contains_key(header_name) {
+            continue;
+        }
+
+        for header_value in standard_headers.get_all(header_name) {
+            customized_headers.append(header_name.clone(), header_value.clone());
+        }
+    }
+
+    Ok(customized_request)
+}
+
+pub async fn make_http_request<P: Serialize + Send>(
+    reqwest_client: &reqwest::Client,
+    request_options: RequestOptions<'_>,
+    request_info: HttpRequest<P>,
+) -> Result<Response, reqwest::Error> {
+    let http_request = build_http_request(reqwest_client, request_options, request_info)?;
+
+    #[cfg(feature = "opentelemetry")]
+    let http_request = super::otel::with_trace_context(http_request);
+
     cfg_if::cfg_if! {
         if #[cfg(feature = "async-compat")] {
             let http_request_future = Compat::new(async {
-                http_request.send().await
+                reqwest_client.execute(http_request).await
             });
         } else {
-            let http_request_future = http_request.send();
+            let http_request_future = reqwest_client.execute(http_request);
         }
     }
 
     http_request_future.await
 }
+
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+
+    use super::*;
+
+    #[test]
+    fn correlation_collision_is_detected_in_server_error() {
+        let server_msg = r#"{"error":"could not set id and public key: correlation-id provided already exists"}"#;
+
+        assert!(is_correlation_collision(server_msg));
+    }
+
+    #[test]
+    fn register_response_without_subdomain_is_ignored() {
+        let register_response = RegisterResponse::parse(r#"{"message":"registration successful"}"#);
+
+        assert!(register_response.assigned_sub_domain("oast.pro").is_none());
+    }
+
+    #[test]
+    fn register_response_correlation_settings_are_parsed() {
+        let register_response = RegisterResponse::parse(
+            r#"{"message":"registration successful","correlation-id-length":20,"correlation-id-nonce-length":13}"#,
+        );
+
+        assert_eq!(register_response.correlation_id_length(), Some(20));
+        assert_eq!(register_response.correlation_id_nonce_length(), Some(13));
+        assert_eq!(
+            register_response.correlation_config(),
+            Some(CorrelationConfig::default())
+        );
+        assert!(register_response.subdomain().is_none());
+    }
+
+    #[test]
+    fn malformed_register_response_is_ignored() {
+        let register_response = RegisterResponse::parse("registration successful");
+
+        assert!(register_response.assigned_sub_domain("oast.pro").is_none());
+    }
+
+    #[test]
+    fn server_assigned_subdomain_is_used() {
+        let register_response = RegisterResponse::parse(
+            r#"{"message":"registration successful","subdomain":"serverchosen"}"#,
+        );
+
+        assert_eq!(
+            register_response.assigned_sub_domain("oast.pro").as_deref(),
+            Some("serverchosen")
+        );
+    }
+
+    #[test]
+    fn server_assigned_subdomain_is_taken_from_fqdn() {
+        let register_response = RegisterResponse::parse(r#"{"fqdn":"serverchosen.oast.pro"}"#);
+
+        assert_eq!(
+            register_response.assigned_sub_domain("oast.pro").as_deref(),
+            Some("serverchosen")
+        );
+        assert!(register_response.assigned_sub_domain("oast.live").is_none());
+    }
+
+    #[test]
+    fn server_base_url_omits_default_port() {
+        assert_eq!(
+            server_base_url("oast.pro", DEFAULT_SERVER_PORT, true),
+            "https://oast.pro"
+        );
+    }
+
+    #[test]
+    fn server_base_url_includes_custom_port() {
+        assert_eq!(
+            server_base_url("interactsh.local", 8443, true),
+            "https://interactsh.local:8443"
+        );
+    }
+
+    #[test]
+    fn server_base_url_uses_http_without_tls() {
+        assert_eq!(
+            server_base_url("localhost", DEFAULT_PLAINTEXT_SERVER_PORT, false),
+            "http://localhost"
+        );
+        assert_eq!(
+            server_base_url("localhost", 8080, false),
+            "http://localhost:8080"
+        );
+    }
+
+    #[test]
+    fn endpoint_url_appends_trailing_slash_when_set() {
+        assert_eq!(
+            endpoint_url("https://oast.pro", "poll", false),
+            "https://oast.pro/poll"
+        );
+        assert_eq!(
+            endpoint_url("https://oast.pro", "poll", true),
+            "https://oast.pro/poll/"
+        );
+    }
+
+    #[test]
+    fn endpoint_paths_are_normalized() {
+        assert_eq!(
+            EndpointPaths::normalize("/interactsh/poll/"),
+            "interactsh/poll"
+        );
+        assert_eq!(EndpointPaths::normalize("poll"), "poll");
+    }
+
+    #[test]
+    fn short_body_snippet_is_not_truncated() {
+        let body = r#"{"aes_key": "#;
+
+        assert_eq!(body_snippet(body), body);
+    }
+
+    #[test]
+    fn long_body_snippet_is_truncated() {
+        let body = "é".repeat(BODY_SNIPPET_MAX_CHARS * 2);
+
+        let snippet = body_snippet(&body);
+
+        assert_eq!(snippet.chars().count(), BODY_SNIPPET_MAX_CHARS + 3);
+        assert!(snippet.ends_with("..."));
+    }
+
+    #[test]
+    fn retry_after_seconds_are_parsed() {
+        let retry_after = parse_retry_after(" 120 ", OffsetDateTime::now_utc());
+
+        assert_eq!(retry_after, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn retry_after_http_date_is_parsed() {
+        let now = OffsetDateTime::from_unix_timestamp(784111747).unwrap();
+
+        let retry_after = parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now);
+        let past_retry_after = parse_retry_after("Sun, 06 Nov 1994 08:48:37 GMT", now);
+
+        assert_eq!(retry_after, Some(Duration::from_secs(30)));
+        assert_eq!(past_retry_after, Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn consecutive_requests_rotate_user_agents() {
+        let user_agents = vec!["first-agent".to_string(), "second-agent".to_string()];
+        let user_agent_rotation = UserAgentRotation::new(user_agents.clone());
+        let reqwest_client = reqwest::Client::new();
+
+        let sent_user_agents: Vec<String> = (0..3)
+            .map(|_| {
+                let request_info =
+                    HttpRequest::new_get_request("https://oast.pro/poll".into(), SmallVec::new());
+                let request_options = RequestOptions {
+                    user_agent: user_agent_rotation.next_user_agent(),
+                    ..Default::default()
+                };
+                let http_request =
+                    build_http_request(&reqwest_client, request_options, request_info)
+                        .expect("Failed to build the request");
+
+                http_request.headers()[USER_AGENT]
+                    .to_str()
+                    .expect("User-Agent header is not valid text")
+                    .to_string()
+            })
+            .collect();
+
+        assert_eq!(
+            sent_user_agents,
+            vec![
+                user_agents[0].clone(),
+                user_agents[1].clone(),
+                user_agents[0].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_headers_are_sent_with_auth_token() {
+        let reqwest_client = reqwest::Client::new();
+        let auth_token = AuthToken::SimpleAuth(Secret::new("auth-token".to_string()));
+        let custom_headers = vec![
+            (
+                HeaderName::from_static("x-api-key"),
+                HeaderValue::from_static("api-key"),
+            ),
+            (
+                HeaderName::from_static("x-route"),
+                HeaderValue::from_static("first"),
+            ),
+            (
+                HeaderName::from_static("x-route"),
+                HeaderValue::from_static("second"),
+            ),
+        ];
+
+        let request_info =
+            HttpRequest::new_get_request("https://oast.pro/poll".into(), SmallVec::new());
+        let request_options = RequestOptions {
+            auth_token: Some(&auth_token),
+            custom_headers: &custom_headers,
+            ..Default::default()
+        };
+        let http_request = build_http_request(&reqwest_client, request_options, request_info)
+            .expect("Failed to build the request");
+
+        let headers = http_request.headers();
+        assert_eq!(headers["x-api-key"], "api-key");
+        assert_eq!(headers["Authorization"], "auth-token");
+        let route_headers: Vec<_> = headers
+            .get_all("x-route")
+            .iter()
+            .map(|header_value| header_value.to_str().expect("Header is not valid text"))
+            .collect();
+        assert_eq!(route_headers, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn bearer_auth_token_is_sent_with_bearer_scheme() {
+        let reqwest_client = reqwest::Client::new();
+        let auth_token = AuthToken::BearerAuth(Secret::new("auth-token".to_string()));
+
+        let request_info =
+            HttpRequest::new_get_request("https://oast.pro/poll".into(), SmallVec::new());
+        let request_options = RequestOptions {
+            auth_token: Some(&auth_token),
+            ..Default::default()
+        };
+        let http_request = build_http_request(&reqwest_client, request_options, request_info)
+            .expect("Failed to build the request");
+
+        assert_eq!(http_request.headers()["Authorization"], "Bearer auth-token");
+    }
+
+    #[test]
+    fn request_timeout_is_applied_when_set() {
+        let reqwest_client = reqwest::Client::new();
+        let poll_timeout = Duration::from_secs(3);
+
+        let request_info =
+            HttpRequest::new_get_request("https://oast.pro/poll".into(), SmallVec::new());
+        let request_options = RequestOptions {
+            timeout: Some(poll_timeout),
+            ..Default::default()
+        };
+        let http_request = build_http_request(&reqwest_client, request_options, request_info)
+            .expect("Failed to build the request");
+
+        assert_eq!(http_request.timeout(), Some(&poll_timeout));
+    }
+
+    #[test]
+    fn request_customizer_headers_replace_standard_headers() {
+        let reqwest_client = reqwest::Client::new();
+        let auth_token = AuthToken::SimpleAuth(Secret::new("auth-token".to_string()));
+        let custom_headers = vec![(
+            HeaderName::from_static("x-api-key"),
+            HeaderValue::from_static("api-key"),
+        )];
+        let request_customizer = RequestCustomizer::new(|http_request| {
+            http_request
+                .header("x-trace-id", "trace-id")
+                .header("Authorization", "signed-auth-token")
+        });
+
+        let request_info =
+            HttpRequest::new_get_request("https://oast.pro/poll".into(), SmallVec::new());
+        let request_options = RequestOptions {
+            auth_token: Some(&auth_token),
+            user_agent: Some("user-agent"),
+            custom_headers: &custom_headers,
+            timeout: None,
+            request_customizer: Some(&request_customizer),
+        };
+        let http_request = build_http_request(&reqwest_client, request_options, request_info)
+            .expect("Failed to build the request");
+
+        let headers = http_request.headers();
+        assert_eq!(headers["x-trace-id"], "trace-id");
+        assert_eq!(headers["x-api-key"], "api-key");
+        assert_eq!(headers[USER_AGENT], "user-agent");
+        let auth_headers: Vec<_> = headers
+            .get_all("Authorization")
+            .iter()
+            .map(|header_value| header_value.to_str().expect("Header is not valid text"))
+            .collect();
+        assert_eq!(auth_headers, vec!["signed-auth-token"]);
+    }
+
+    #[test]
+    fn empty_user_agent_rotation_returns_none() {
+        let user_agent_rotation = UserAgentRotation::default();
+
+        assert!(user_agent_rotation.next_user_agent().is_none());
+    }
+
+    #[test]
+    fn other_server_errors_are_not_correlation_collisions() {
+        let server_msg = r#"{"error":"could not decode json body"}"#;
+
+        assert!(!is_correlation_collision(server_msg));
+    }
+}
@@ -0,0 +1,162 @@
+//! Scheduling for the optional keepalive requests sent between polls to keep
+//! the connection to the Interactsh server warm.
+
+use std::future::Future;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures_timer::Delay;
+use rand::Rng;
+
+
+/// Splits the wait between two polls into the intervals to sleep for.
+///
+/// A keepalive request is sent after every interval except the last one,
+/// since the next poll immediately follows it. If no keepalive interval is
+/// set (or the interval is zero), the whole wait is a single interval.
+pub(crate) fn keepalive_schedule(
+    poll_interval: Duration,
+    keepalive_interval: Option<Duration>,
+) -> Vec<Duration> {
+    let keepalive_interval = match keepalive_interval {
+        Some(interval) if !interval.is_zero() && interval < poll_interval => interval,
+        _ => return vec![poll_interval],
+    };
+
+    let mut schedule = Vec::new();
+    let mut remaining = poll_interval;
+    while !remaining.is_zero() {
+        let next_interval = remaining.min(keepalive_interval);
+        schedule.push(next_interval);
+        remaining -= next_interval;
+    }
+
+    schedule
+}
+
+/// Applies uniform random jitter of up to +/- `jitter_fraction` of the poll
+/// interval, so that many clients polling on the same interval spread out
+/// their requests.
+///
+/// The fraction is clamped between 0 and 1.
+pub(crate) fn jittered_interval(poll_interval: Duration, jitter_fraction: f64) -> Duration {
+    let jitter_fraction = if jitter_fraction.is_nan() {
+        0.0
+    } else {
+        jitter_fraction.clamp(0.0, 1.0)
+    };
+    if jitter_fraction == 0.0 {
+        return poll_interval;
+    }
+
+    let scale = rand::thread_rng().gen_range(1.0 - jitter_fraction..=1.0 + jitter_fraction);
+
+    poll_interval.mul_f64(scale)
+}
+
+/// Sleeps for the provided duration without depending on a specific
+/// async runtime
+pub(crate) async fn sleep(duration: Duration) {
+    Delay::new(duration).await;
+}
+
+/// Runs the future until it completes or the cancel future resolves,
+/// whichever happens first.
+///
+/// Returns true if the future completed, or false if it was cancelled.
+pub(crate) async fn run_until_cancelled<F, C>(future: F, cancel: C) -> bool
+where
+    F: Future<Output = ()>,
+    C: Future<Output = ()>,
+{
+    let mut future = Box::pin(future);
+    let mut cancel = Box::pin(cancel);
+
+    std::future::poll_fn(|cx| {
+        if cancel.as_mut().poll(cx).is_ready() {
+            Poll::Ready(false)
+        } else if future.as_mut().poll(cx).is_ready() {
+            Poll::Ready(true)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keepalive_schedule_without_interval_is_single_wait() {
+        let poll_interval = Duration::from_secs(10);
+
+        let schedule = keepalive_schedule(poll_interval, None);
+
+        assert_eq!(schedule, vec![poll_interval]);
+    }
+
+    #[test]
+    fn keepalive_schedule_fires_on_interval() {
+        let schedule = keepalive_schedule(Duration::from_secs(10), Some(Duration::from_secs(3)));
+
+        // A keepalive is sent after each of the first 3 intervals
+        assert_eq!(
+            schedule,
+            vec![
+                Duration::from_secs(3),
+                Duration::from_secs(3),
+                Duration::from_secs(3),
+                Duration::from_secs(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn keepalive_schedule_ignores_zero_interval() {
+        let poll_interval = Duration::from_secs(10);
+
+        let schedule = keepalive_schedule(poll_interval, Some(Duration::ZERO));
+
+        assert_eq!(schedule, vec![poll_interval]);
+    }
+
+    #[test]
+    fn jittered_interval_stays_within_fraction() {
+        let poll_interval = Duration::from_secs(10);
+
+        for _ in 0..100 {
+            let interval = jittered_interval(poll_interval, 0.2);
+
+            assert!(interval >= Duration::from_secs(8));
+            assert!(interval <= Duration::from_secs(12));
+        }
+    }
+
+    #[test]
+    fn jittered_interval_without_fraction_is_unchanged() {
+        let poll_interval = Duration::from_secs(10);
+
+        assert_eq!(jittered_interval(poll_interval, 0.0), poll_interval);
+        assert_eq!(jittered_interval(poll_interval, -1.0), poll_interval);
+        assert_eq!(jittered_interval(poll_interval, f64::NAN), poll_interval);
+    }
+
+    #[tokio::test]
+    async fn run_until_cancelled_stops_when_cancel_resolves() {
+        let completed =
+            run_until_cancelled(sleep(Duration::from_secs(60)), sleep(Duration::ZERO)).await;
+
+        assert!(!completed);
+    }
+
+    #[tokio::test]
+    async fn run_until_cancelled_completes_without_cancel() {
+        let completed =
+            run_until_cancelled(sleep(Duration::ZERO), std::future::pending::<()>()).await;
+
+        assert!(completed);
+    }
+}
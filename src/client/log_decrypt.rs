@@ -0,0 +1,474 @@
+//! Defines the functions used to decode and decrypt the logs returned when
+//! polling an Interactsh server.
+
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use snafu::ResultExt;
+use zeroize::Zeroizing;
+
+use super::errors::{client_poll_error, ClientPollError};
+use crate::crypto::aes;
+use crate::crypto::rsa::{OaepHash, RSAPrivKey, RsaPadding};
+use crate::interaction_log::{self, LogEntry};
+
+
+/// The logs returned by [poll_lenient](crate::client::RegisteredClient::poll_lenient())
+#[derive(Debug, Default)]
+pub struct LenientLogs {
+    /// The logs that were decrypted successfully, in the order the server
+    /// sent them
+    pub logs: Vec<LogEntry>,
+
+    /// The logs that failed to decrypt, as the index of the log in the poll
+    /// response along with the error
+    pub failed_logs: Vec<(usize, ClientPollError)>,
+}
+
+/// Decrypts the logs of a poll response that was fetched outside of a
+/// [RegisteredClient](crate::client::RegisteredClient), such as by a custom
+/// polling layer.
+///
+/// `aes_key` is the base 64 encoded and RSA encrypted AES key returned by the
+/// server (the `aes_key` field of the poll response), and `data_list` is the
+/// list of base 64 encoded and encrypted logs (the `data` field). The AES key
+/// is decrypted with the provided RSA private key, padding scheme, and OAEP
+/// hash. The Interactsh server uses [RsaPadding::Oaep] with
+/// [OaepHash::Sha256] by default. If `parse_logs` is true, the logs are
+/// parsed the same way as with
+/// [parse_log_entry](crate::interaction_log::parse_log_entry()).
+pub fn decrypt_poll_response(
+    aes_key: &str,
+    data_list: &[String],
+    rsa_key: &RSAPrivKey,
+    rsa_padding: RsaPadding,
+    oaep_hash: OaepHash,
+    parse_logs: bool,
+) -> Result<Vec<LogEntry>, ClientPollError> {
+    let aes_plain_key = decrypt_aes_key(rsa_key, aes_key, rsa_padding, oaep_hash)?;
+
+    decrypt_logs(&aes_plain_key, data_list, parse_logs)
+}
+
+/// Decodes the base 64 encoded AES key returned by the server and decrypts
+/// it with the client's RSA private key
+///
+/// The plain-text AES key is zeroized when dropped.
+pub(crate) fn decrypt_aes_key(
+    rsa_key: &RSAPrivKey,
+    encoded_aes_key: &str,
+    rsa_padding: RsaPadding,
+    oaep_hash: OaepHash,
+) -> Result<Zeroizing<Vec<u8>>, ClientPollError> {
+    let aes_key_decoded =
+        decode_base64_lenient(encoded_aes_key).context(client_poll_error::Base64DecodeFailed)?;
+
+    let aes_plain_key = rsa_key
+        .decrypt_data(&aes_key_decoded, rsa_padding, oaep_hash)
+        .context(client_poll_error::AesKeyDecryptFailed)?;
+
+    Ok(aes_plain_key)
+}
+
+/// Decodes base 64 data that may contain whitespace or use the URL-safe alphabet
+///
+/// Some servers wrap the encoded AES key across multiple lines, so all
+/// whitespace is stripped before decoding. If the standard alphabet fails,
+/// the URL-safe alphabet is tried before returning the original error.
+fn decode_base64_lenient(encoded_data: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let stripped_data: String = encoded_data
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+
+    general_purpose::STANDARD
+        .decode(&stripped_data)
+        .or_else(|standard_error| {
+            general_purpose::URL_SAFE
+                .decode(&stripped_data)
+                .map_err(|_| standard_error)
+        })
+}
+
+/// Decodes and decrypts each of the base 64 encoded logs returned by the server
+/// using the plain-text AES key
+///
+/// With the `parallel-decrypt` feature, the logs are decrypted in parallel on
+/// the rayon thread pool. The logs are returned in the order the server sent
+/// them either way.
+pub(crate) fn decrypt_logs(
+    aes_key: &[u8],
+    data_list: &[String],
+    parse_logs: bool,
+) -> Result<Vec<LogEntry>, ClientPollError> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "parallel-decrypt")] {
+            use rayon::prelude::*;
+
+            data_list
+                .par_iter()
+                .map(|data| decrypt_log(aes_key, data, parse_logs))
+                .collect()
+        } else {
+            let mut results = Vec::new();
+            for data in data_list.iter() {
+                let log_entry = decrypt_log(aes_key, data, parse_logs)?;
+                results.push(log_entry);
+            }
+
+            Ok(results)
+        }
+    }
+}
+
+/// Decodes and decrypts each of the base 64 encoded logs returned by the server
+/// using the plain-text AES key, continuing past logs that fail to decrypt
+///
+/// The logs that failed are returned with their index in the data list. As
+/// with [decrypt_logs], the `parallel-decrypt` feature decrypts the logs in
+/// parallel.
+pub(crate) fn decrypt_logs_lenient(
+    aes_key: &[u8],
+    data_list: &[String],
+    parse_logs: bool,
+) -> LenientLogs {
+    let results: Vec<Result<LogEntry, ClientPollError>> = {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "parallel-decrypt")] {
+                use rayon::prelude::*;
+
+                data_list
+                    .par_iter()
+                    .map(|data| decrypt_log(aes_key, data, parse_logs))
+                    .collect()
+            } else {
+                data_list
+                    .iter()
+                    .map(|data| decrypt_log(aes_key, data, parse_logs))
+                    .collect()
+            }
+        }
+    };
+
+    let mut lenient_logs = LenientLogs::default();
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(log_entry) => lenient_logs.logs.push(log_entry),
+            Err(error) => lenient_logs.failed_logs.push((index, error)),
+        }
+    }
+
+    lenient_logs
+}
+
+/// Decodes and decrypts a single log using the plain-text AES key
+fn decrypt_log(aes_key: &[u8], data: &str, parse_logs: bool) -> Result<LogEntry, ClientPollError> {
+    let data_decoded = general_purpose::STANDARD
+        .decode(data)
+        .context(client_poll_error::Base64DecodeFailed)?;
+
+    let decrypted_data = aes::decrypt_data(aes_key, &data_decoded)
+        .context(client_poll_error::DataDecryptFailed)?;
+
+    // Logs that aren't valid UTF-8 can't be parsed, so keep the original bytes
+    let log_entry = match std::str::from_utf8(&decrypted_data) {
+        Ok(decrypted_string) => interaction_log::parse_log_entry(decrypted_string, parse_logs),
+        Err(_) => LogEntry::return_raw_bytes_log(&decrypted_data),
+    };
+
+    Ok(log_entry)
+}
+
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    //! Helpers for generating encrypted poll data in tests
+    use rand::RngCore;
+
+    use super::*;
+    use crate::crypto::rsa::RSAPubKey;
+
+    /// Encrypts the provided logs the same way an Interactsh server does,
+    /// returning the encoded AES key and the list of encoded logs
+    pub(crate) fn encrypt_logs(pub_key: &RSAPubKey, logs: &[&str]) -> (String, Vec<String>) {
+        encrypt_logs_with_padding(pub_key, logs, RsaPadding::Oaep, OaepHash::default())
+    }
+
+    /// Encrypts the provided logs, encrypting the AES key with the provided
+    /// RSA padding scheme and OAEP hash
+    pub(crate) fn encrypt_logs_with_padding(
+        pub_key: &RSAPubKey,
+        logs: &[&str],
+        rsa_padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> (String, Vec<String>) {
+        let mut rng = rand::thread_rng();
+
+        let mut aes_key = [0u8; 32];
+        rng.fill_bytes(&mut aes_key);
+
+        let encrypted_aes_key = pub_key.encrypt_data(&aes_key, rsa_padding, oaep_hash);
+        let encoded_aes_key = general_purpose::STANDARD.encode(encrypted_aes_key);
+
+        let encoded_logs = logs
+            .iter()
+            .map(|log| {
+                let mut iv = [0u8; 16];
+                rng.fill_bytes(&mut iv);
+
+                let encrypted_log = aes::encrypt_data(&aes_key, &iv, log.as_bytes());
+                general_purpose::STANDARD.encode(encrypted_log)
+            })
+            .collect();
+
+        (encoded_aes_key, encoded_logs)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::{encrypt_logs, encrypt_logs_with_padding};
+    use super::*;
+
+    #[test]
+    fn encrypted_logs_decrypt_successfully() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+        let logs = ["first log", "second log"];
+
+        let (encoded_aes_key, encoded_logs) = encrypt_logs(&pub_key, &logs);
+
+        let aes_key = decrypt_aes_key(
+            &rsa_key,
+            &encoded_aes_key,
+            RsaPadding::Oaep,
+            OaepHash::default(),
+        )
+        .expect("Failed to decrypt the AES key");
+        let decrypted_logs =
+            decrypt_logs(&aes_key, &encoded_logs, false).expect("Failed to decrypt the logs");
+
+        assert_eq!(decrypted_logs.len(), logs.len());
+        for (log_entry, log) in decrypted_logs.iter().zip(logs.iter()) {
+            match log_entry {
+                LogEntry::RawLog(raw_log) => assert_eq!(raw_log.log_entry, *log),
+                LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+            }
+        }
+    }
+
+    #[test]
+    fn poll_response_decrypts_with_public_function() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let (encoded_aes_key, encoded_logs) = encrypt_logs(&pub_key, &["first log", "second log"]);
+
+        let decrypted_logs = decrypt_poll_response(
+            &encoded_aes_key,
+            &encoded_logs,
+            &rsa_key,
+            RsaPadding::Oaep,
+            OaepHash::default(),
+            true,
+        )
+        .expect("Failed to decrypt the poll response");
+
+        assert_eq!(decrypted_logs.len(), 2);
+        assert!(decrypted_logs[0].same_interaction(&LogEntry::return_raw_log("first log")));
+    }
+
+    #[test]
+    fn poll_response_decrypts_with_non_default_padding_and_hash() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let (encoded_aes_key, encoded_logs) =
+            encrypt_logs_with_padding(&pub_key, &["log"], RsaPadding::Oaep, OaepHash::Sha1);
+
+        let decrypted_logs = decrypt_poll_response(
+            &encoded_aes_key,
+            &encoded_logs,
+            &rsa_key,
+            RsaPadding::Oaep,
+            OaepHash::Sha1,
+            false,
+        )
+        .expect("Failed to decrypt the poll response");
+        assert!(decrypted_logs[0].same_interaction(&LogEntry::return_raw_log("log")));
+
+        let default_hash_result = decrypt_poll_response(
+            &encoded_aes_key,
+            &encoded_logs,
+            &rsa_key,
+            RsaPadding::Oaep,
+            OaepHash::default(),
+            false,
+        );
+        assert!(matches!(
+            default_hash_result,
+            Err(ClientPollError::AesKeyDecryptFailed { .. })
+        ));
+
+        let (encoded_aes_key, encoded_logs) =
+            encrypt_logs_with_padding(&pub_key, &["log"], RsaPadding::Pkcs1v15, OaepHash::default());
+
+        let decrypted_logs = decrypt_poll_response(
+            &encoded_aes_key,
+            &encoded_logs,
+            &rsa_key,
+            RsaPadding::Pkcs1v15,
+            OaepHash::default(),
+            false,
+        )
+        .expect("Failed to decrypt the poll response");
+        assert!(decrypted_logs[0].same_interaction(&LogEntry::return_raw_log("log")));
+    }
+
+    #[test]
+    fn pkcs1v15_encrypted_aes_key_decrypts_successfully() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let (encoded_aes_key, _encoded_logs) =
+            encrypt_logs_with_padding(&pub_key, &["log"], RsaPadding::Pkcs1v15, OaepHash::default());
+
+        let aes_key = decrypt_aes_key(
+            &rsa_key,
+            &encoded_aes_key,
+            RsaPadding::Pkcs1v15,
+            OaepHash::default(),
+        )
+        .expect("Failed to decrypt the AES key");
+
+        assert_eq!(aes_key.len(), 32);
+    }
+
+    #[test]
+    fn aes_key_with_embedded_newlines_decrypts_successfully() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let (encoded_aes_key, _encoded_logs) = encrypt_logs(&pub_key, &["log"]);
+        let wrapped_aes_key = encoded_aes_key
+            .as_bytes()
+            .chunks(64)
+            .map(|line| std::str::from_utf8(line).expect("Base 64 is not valid text"))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        let padded_aes_key = format!(" {}\n", wrapped_aes_key);
+
+        let aes_key = decrypt_aes_key(
+            &rsa_key,
+            &padded_aes_key,
+            RsaPadding::Oaep,
+            OaepHash::default(),
+        )
+        .expect("Failed to decrypt the AES key");
+
+        assert_eq!(aes_key.len(), 32);
+    }
+
+    #[test]
+    fn url_safe_base64_decodes_successfully() {
+        let data = [0xfb, 0xff, 0xfe];
+        let encoded_data = general_purpose::URL_SAFE.encode(data);
+
+        let decoded_data = decode_base64_lenient(&encoded_data).expect("Failed to decode");
+
+        assert_eq!(decoded_data, data);
+    }
+
+    #[test]
+    fn invalid_base64_fails_to_decode() {
+        assert!(decode_base64_lenient("not base 64!").is_err());
+    }
+
+    #[test]
+    fn decrypted_logs_keep_server_order() {
+        let rsa_key = RSAPrivKey::generate(2048).expect("RSA key generation failed");
+        let pub_key = rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+        let logs = (0..20).map(|index| index.to_string()).collect::<Vec<_>>();
+        let log_strs = logs.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let (encoded_aes_key, encoded_logs) = encrypt_logs(&pub_key, &log_strs);
+
+        let aes_key = decrypt_aes_key(
+            &rsa_key,
+            &encoded_aes_key,
+            RsaPadding::Oaep,
+            OaepHash::default(),
+        )
+        .expect("Failed to decrypt the AES key");
+        let decrypted_logs =
+            decrypt_logs(&aes_key, &encoded_logs, false).expect("Failed to decrypt the logs");
+
+        let decrypted_strings = decrypted_logs
+            .iter()
+            .map(|log_entry| match log_entry {
+                LogEntry::RawLog(raw_log) => raw_log.log_entry.clone(),
+                LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(decrypted_strings, logs);
+    }
+
+    #[test]
+    fn invalid_utf8_log_keeps_original_bytes() {
+        let aes_key = [5u8; 32];
+        let log_bytes = b"SMB\xff\xfe\x00data";
+        let encrypted_log = aes::encrypt_data(&aes_key, &[9u8; 16], log_bytes);
+        let encoded_log = general_purpose::STANDARD.encode(encrypted_log);
+
+        let log_entry =
+            decrypt_log(&aes_key, &encoded_log, true).expect("Failed to decrypt the log");
+
+        match log_entry {
+            LogEntry::RawLog(raw_log) => {
+                assert!(raw_log.is_lossy());
+                assert_eq!(raw_log.as_bytes(), log_bytes);
+                assert_eq!(raw_log.log_entry, "SMB\u{fffd}\u{fffd}\u{0}data");
+            }
+            LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+        }
+    }
+
+    #[test]
+    fn lenient_decryption_keeps_logs_around_failed_log() {
+        let aes_key = [5u8; 32];
+        let encoded_logs = ["first log", "second log"]
+            .iter()
+            .map(|log| {
+                let encrypted_log = aes::encrypt_data(&aes_key, &[9u8; 16], log.as_bytes());
+                general_purpose::STANDARD.encode(encrypted_log)
+            })
+            .collect::<Vec<_>>();
+        let data_list = vec![
+            encoded_logs[0].clone(),
+            "not base 64!".to_string(),
+            encoded_logs[1].clone(),
+        ];
+
+        let lenient_logs = decrypt_logs_lenient(&aes_key, &data_list, false);
+
+        assert_eq!(lenient_logs.logs.len(), 2);
+        assert!(lenient_logs.logs[1].same_interaction(&LogEntry::return_raw_log("second log")));
+        assert_eq!(lenient_logs.failed_logs.len(), 1);
+        assert!(matches!(
+            lenient_logs.failed_logs[0],
+            (1, ClientPollError::Base64DecodeFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+//! A minimal HTTP server used to test the requests the clients send and how
+//! they handle the server's responses.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use super::builder::ClientBuilder;
+
+
+/// A request received by the [MockServer]
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    // Only read by the tests of the opentelemetry feature so far
+    #[cfg_attr(not(feature = "opentelemetry"), allow(dead_code))]
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: String,
+}
+
+impl RecordedRequest {
+    /// Gets the value of the first header with the name, ignoring case
+    #[cfg_attr(not(feature = "opentelemetry"), allow(dead_code))]
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, header_value)| header_value.as_str())
+    }
+
+    /// Parses the body of the request as JSON
+    pub(crate) fn json_body(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("Request body is not valid JSON")
+    }
+}
+
+/// A response sent by the [MockServer]
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    status: u16,
+    body: String,
+}
+
+impl MockResponse {
+    /// Creates a 200 OK response with the provided body
+    pub(crate) fn ok(body: impl Into<String>) -> Self {
+        Self::status(200, body)
+    }
+
+    /// Creates a response with the provided status code and body
+    pub(crate) fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+}
+
+/// State shared between the [MockServer] and its listener thread
+#[derive(Default)]
+struct MockState {
+    responses: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// HTTP server listening on a random local port, which replies to each
+/// request with the responses queued for its path
+///
+/// Each connection serves a single request and is then closed.
+pub(crate) struct MockServer {
+    port: u16,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    /// Starts the server on a separate thread
+    pub(crate) fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the mock server");
+        let port = listener
+            .local_addr()
+            .expect("Mock server has no local address")
+            .port();
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_state = Arc::clone(&state);
+        let thread_shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                if let Ok(stream) = stream {
+                    handle_connection(stream, &thread_state);
+                }
+            }
+        });
+
+        Self {
+            port,
+            state,
+            shutdown,
+        }
+    }
+
+    /// Gets a builder for a client that sends its requests to this server
+    pub(crate) fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new()
+            .with_server("127.0.0.1".into())
+            .with_server_port(self.port)
+            .use_tls(false)
+            .with_rsa_key_size(2048)
+    }
+
+    /// Queues a response for requests to the path (ex. "/register").
+    ///
+    /// Queued responses are sent in order, and the last one is repeated for
+    /// any further requests. Requests to a path without a queued response
+    /// get a 404 response.
+    pub(crate) fn queue_response(&self, path: &str, response: MockResponse) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state
+            .responses
+            .entry(path.to_owned())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Gets the requests received for the path, in the order they arrived
+    pub(crate) fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state
+            .requests
+            .iter()
+            .filter(|request| request.path == path)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        // Wake the listener thread so that it sees the shutdown flag
+        self.shutdown.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+    }
+}
+
+/// Reads a single request from the connection, records it, and sends the
+/// next response queued for its path
+fn handle_connection(mut stream: TcpStream, state: &Mutex<MockState>) {
+    let request = match read_request(&stream) {
+        Some(request) => request,
+        None => return,
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let response = match state.responses.get_mut(&request.path) {
+            Some(responses) if responses.len() > 1 => responses.pop_front(),
+            Some(responses) => responses.front().cloned(),
+            None => None,
+        };
+        state.requests.push(request.clone());
+
+        response.unwrap_or_else(|| MockResponse::status(404, "not found"))
+    };
+
+    let body = if request.method == "HEAD" {
+        ""
+    } else {
+        response.body.as_str()
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        body.len(),
+        body
+    );
+}
+
+/// Reads the request line, headers, and body of an HTTP request
+fn read_request(stream: &TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut request_parts = request_line.split_whitespace();
+    let method = request_parts.next()?.to_owned();
+    let target = request_parts.next()?;
+    let path = target.split('?').next().unwrap_or(target);
+
+    let mut headers = Vec::new();
+    let mut content_length = 0;
+    loop {
+        let mut header_line = String::new();
+        reader.read_line(&mut header_line).ok()?;
+        let header_line = header_line.trim_end();
+        if header_line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header_line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path: path.to_owned(),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
@@ -8,6 +8,8 @@
 //! Once you are done with the client, you should call the
 //! [deregister](RegisteredClient::deregister()) function on the client
 //! to deregister with the Interactsh server and dispose of the client.
+//! For quick scripts, [quick_client] builds a client with the default options
+//! and registers it in one call.
 //!
 //! The [poll](RegisteredClient::poll()) function can be used to poll the server
 //! once successfully registered. If there are new logs, they will be
@@ -94,11 +96,37 @@
 
 pub(crate) mod http_utils;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod blocking_task;
 mod builder;
+#[cfg(feature = "cert-pinning")]
+mod cert_pinning;
+mod correlation;
+mod dedup;
+#[cfg(feature = "async-compat")]
+mod deregister_guard;
+mod keepalive;
 pub(crate) mod errors;
+mod history;
+mod log_decrypt;
+#[cfg(test)]
+mod mock_server;
+#[cfg(feature = "opentelemetry")]
+mod otel;
 mod registered;
+mod session;
+mod sink;
+#[cfg(test)]
+mod test_cert;
 mod unregistered;
 
 pub use builder::*;
+pub use correlation::{CorrelationConfig, InteractionFqdn};
+pub use dedup::*;
+pub use http_utils::RegisterResponse;
+pub use log_decrypt::{decrypt_poll_response, LenientLogs};
 pub use registered::*;
+pub use session::*;
+pub use sink::*;
 pub use unregistered::*;
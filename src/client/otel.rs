@@ -0,0 +1,107 @@
+//! OpenTelemetry context propagation, used when the crate is built with the
+//! `opentelemetry` feature flag.
+
+use opentelemetry::propagation::Injector;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Request;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+
+/// Writes the fields of a propagated context into request headers
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let header_name = HeaderName::from_bytes(key.as_bytes());
+        let header_value = HeaderValue::from_str(&value);
+
+        if let (Ok(header_name), Ok(header_value)) = (header_name, header_value) {
+            self.0.insert(header_name, header_value);
+        }
+    }
+}
+
+/// Adds the trace context of the current span to the request headers, using
+/// the globally registered text map propagator
+///
+/// Nothing is added if no propagator was registered with
+/// [opentelemetry::global::set_text_map_propagator].
+pub(crate) fn with_trace_context(mut request: Request) -> Request {
+    let context = tracing::Span::current().context();
+
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(request.headers_mut()));
+    });
+
+    request
+}
+
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use crate::client::mock_server::{MockResponse, MockServer};
+
+    /// Gets the value of an attribute recorded on the span as a string
+    fn span_attribute(span: &SpanData, key: &str) -> Option<String> {
+        span.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == key)
+            .map(|attribute| attribute.value.to_string())
+    }
+
+    #[tokio::test]
+    async fn poll_is_recorded_as_a_span_and_propagated() {
+        let exporter = InMemorySpanExporter::default();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("interactsh-rs")),
+        );
+        let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let server = MockServer::start();
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "", "data": []}"#));
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        client.poll().await.expect("Poll of the mock server failed");
+
+        let finished_spans = exporter
+            .get_finished_spans()
+            .expect("Failed to get the finished spans");
+        let poll_span = finished_spans
+            .iter()
+            .find(|span| span.name == "poll")
+            .expect("No span was recorded for the poll");
+        assert_eq!(
+            span_attribute(poll_span, "peer.service").as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(
+            span_attribute(poll_span, "http.status_code").as_deref(),
+            Some("200")
+        );
+        assert_eq!(
+            span_attribute(poll_span, "interactsh.log_count").as_deref(),
+            Some("0")
+        );
+
+        let poll_requests = server.requests_to("/poll");
+        let traceparent = poll_requests[0]
+            .header("traceparent")
+            .expect("No trace context was sent with the poll");
+        let trace_id = poll_span.span_context.trace_id().to_string();
+        assert!(traceparent.contains(&trace_id));
+    }
+}
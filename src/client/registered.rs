@@ -1,19 +1,60 @@
-use base64::engine::general_purpose;
-use base64::Engine as _;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use rand::distributions::{Alphanumeric, DistString};
+use rand::Rng;
+use reqwest::header::{HeaderName, HeaderValue, RETRY_AFTER};
+use reqwest::StatusCode;
 use secrecy::{ExposeSecret, Secret};
 use smallvec::SmallVec;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
+use time::OffsetDateTime;
 
+use super::builder::AuthToken;
+use super::correlation::InteractionFqdn;
+#[cfg(feature = "async-compat")]
+use super::deregister_guard::DeregisterGuard;
 use super::errors::{
     client_poll_error,
     client_registration_error,
     ClientPollError,
     ClientRegistrationError,
+    RegistrationError,
+};
+use super::history::LogHistory;
+use super::http_utils::{
+    self,
+    Client,
+    DeregisterData,
+    EndpointPaths,
+    HttpRequest,
+    PollResponse,
+    RegisterResponse,
+    RequestCustomizer,
+    RequestOptions,
+    UserAgentRotation,
 };
-use super::http_utils::{self, Client, DeregisterData, HttpRequest, PollResponse};
-use crate::crypto::aes;
-use crate::crypto::rsa::RSAPrivKey;
-use crate::interaction_log::LogEntry;
+use super::log_decrypt::LenientLogs;
+use super::session::SessionState;
+use super::sink::LogSinks;
+use super::{keepalive, log_decrypt};
+use crate::crypto::errors::CryptoError;
+use crate::crypto::rsa::{OaepHash, RSAPrivKey, RsaPadding};
+use crate::interaction_log::{self, LogEntry, LogProtocol};
+
+/// Maximum length of a full domain name
+const MAX_DOMAIN_NAME_LENGTH: usize = 253;
+
+/// Maximum length of a single label in a domain name
+const MAX_DNS_LABEL_LENGTH: usize = 63;
+
+/// Length of the random labels generated for payloads
+const PAYLOAD_LABEL_LENGTH: usize = 8;
+
+/// Maximum exponent used when doubling the retry delay, to avoid overflow
+const MAX_BACKOFF_EXPONENT: u32 = 16;
 
 /// The client type returned when an [UnregisteredClient](crate::client::UnregisteredClient)
 /// successfully registers with its configured Interactsh server.
@@ -21,12 +62,103 @@ use crate::interaction_log::LogEntry;
 pub struct RegisteredClient {
     pub(crate) rsa_key: RSAPrivKey,
     pub(crate) server: String,
+    pub(crate) server_url: String,
+    pub(crate) trailing_slash: bool,
+    pub(crate) endpoint_paths: EndpointPaths,
     pub(crate) sub_domain: String,
     pub(crate) correlation_id: String,
-    pub(crate) auth_token: Option<Secret<String>>,
+    pub(crate) auth_token: Option<AuthToken>,
     pub(crate) secret_key: Secret<String>,
     pub(crate) reqwest_client: reqwest::Client,
     pub(crate) parse_logs: bool,
+    pub(crate) retry_on_parse_failure: bool,
+    pub(crate) log_sinks: LogSinks,
+    pub(crate) keepalive_interval: Option<Duration>,
+    pub(crate) poll_timeout: Option<Duration>,
+    pub(crate) user_agents: UserAgentRotation,
+    pub(crate) custom_headers: Vec<(HeaderName, HeaderValue)>,
+    pub(crate) request_customizer: Option<RequestCustomizer>,
+    pub(crate) rsa_padding: RsaPadding,
+    pub(crate) oaep_hash: OaepHash,
+    pub(crate) log_history: LogHistory,
+    pub(crate) register_response: RegisterResponse,
+    pub(crate) registered: bool,
+    pub(crate) last_decrypt_duration: Arc<Mutex<Option<Duration>>>,
+    #[cfg(feature = "async-compat")]
+    pub(crate) deregister_guard: Option<Arc<DeregisterGuard>>,
+    #[cfg(all(feature = "test-utils", debug_assertions))]
+    pub(crate) last_aes_key: LastAesKey,
+}
+
+/// The logs collected by [collect_logs](RegisteredClient::collect_logs()),
+/// along with the error that stopped polling early, if any
+#[derive(Debug)]
+pub struct PolledLogs {
+    /// All logs polled from the server before polling stopped
+    pub logs: Vec<LogEntry>,
+
+    /// The error that stopped polling early, if any
+    pub poll_error: Option<ClientPollError>,
+}
+
+/// The log found by [wait_for_interaction](RegisteredClient::wait_for_interaction())
+#[derive(Debug)]
+pub struct InteractionMatch {
+    /// The first log that matched
+    pub log: LogEntry,
+
+    /// The other logs returned by the same poll as the matching log, in the
+    /// order the server sent them
+    pub other_logs: Vec<LogEntry>,
+}
+
+/// The logs collected by
+/// [collect_and_deregister](RegisteredClient::collect_and_deregister()),
+/// along with any errors that occured while polling or deregistering.
+#[derive(Debug)]
+pub struct CollectedLogs {
+    /// All logs polled from the server before polling stopped
+    pub logs: Vec<LogEntry>,
+
+    /// The error that stopped polling early, if any
+    pub poll_error: Option<ClientPollError>,
+
+    /// The error returned when deregistering, if any
+    pub deregister_error: Option<ClientRegistrationError<RegisteredClient>>,
+}
+
+/// The most recently decrypted AES key, shared between clones of a client
+///
+/// See [last_decrypted_aes_key](RegisteredClient::last_decrypted_aes_key()).
+#[cfg(all(feature = "test-utils", debug_assertions))]
+#[derive(Clone, Default)]
+pub(crate) struct LastAesKey {
+    aes_key: Arc<Mutex<Option<Secret<Vec<u8>>>>>,
+}
+
+#[cfg(all(feature = "test-utils", debug_assertions))]
+impl LastAesKey {
+    /// Gets a copy of the stored AES key, if any
+    fn get(&self) -> Option<Secret<Vec<u8>>> {
+        let aes_key = self.aes_key.lock().unwrap_or_else(PoisonError::into_inner);
+
+        aes_key
+            .as_ref()
+            .map(|aes_key| Secret::new(aes_key.expose_secret().clone()))
+    }
+
+    /// Replaces the stored AES key
+    fn set(&self, aes_key: &[u8]) {
+        let mut stored_aes_key = self.aes_key.lock().unwrap_or_else(PoisonError::into_inner);
+        *stored_aes_key = Some(Secret::new(aes_key.to_vec()));
+    }
+}
+
+#[cfg(all(feature = "test-utils", debug_assertions))]
+impl std::fmt::Debug for LastAesKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Last AES Key>")
+    }
 }
 
 impl RegisteredClient {
@@ -48,44 +180,621 @@ impl RegisteredClient {
         format!("{}.{}", self.sub_domain, self.server)
     }
 
+    /// Gets the interaction FQDN for the current registered session, split
+    /// into its subdomain and server domain
+    pub fn get_typed_interaction_fqdn(&self) -> InteractionFqdn {
+        InteractionFqdn::new(&self.sub_domain, &self.server)
+    }
+
+    /// Gets the correlation ID for the current
+    /// registered session
+    pub fn get_correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+
+    /// Gets the subdomain (the part of the interaction FQDN
+    /// before the server domain) for the current registered session
+    pub fn get_subdomain(&self) -> &str {
+        &self.sub_domain
+    }
+
+    /// Gets the data returned by the server when the client registered.
+    ///
+    /// This is empty if the server didn't return a JSON body (as with the
+    /// public servers), or if the client was restored from a session.
+    pub fn get_register_response(&self) -> &RegisterResponse {
+        &self.register_response
+    }
+
+    /// Gets the maximum length of a label that can be prepended to the
+    /// interaction FQDN (as `<label>.<interaction FQDN>`) without exceeding
+    /// the DNS length limits for a label or a full domain name.
+    pub fn max_payload_label_len(&self) -> usize {
+        payload_label_budget(&self.get_interaction_fqdn())
+    }
+
+    /// Builds a payload URL pointing to the interaction FQDN with the
+    /// provided scheme and path, such as `https://<interaction FQDN>/path?x=1`.
+    ///
+    /// A leading slash on the path is optional. Returns None if the client
+    /// was deregistered, since the server no longer records interactions
+    /// with its FQDN.
+    pub fn build_payload_url(&self, scheme: &str, path: &str) -> Option<String> {
+        self.registered
+            .then(|| self.get_typed_interaction_fqdn().url(scheme, path))
+    }
+
+    /// Builds a DNS payload by prepending the label to the interaction FQDN
+    /// (as `<label>.<interaction FQDN>`).
+    ///
+    /// Leading and trailing dots on the label are ignored. The label is not
+    /// checked against [max_payload_label_len](RegisteredClient::max_payload_label_len()).
+    /// Returns None if the client was deregistered.
+    pub fn build_dns_payload(&self, label: &str) -> Option<String> {
+        self.registered.then(|| self.format_dns_payload(label))
+    }
+
+    /// Prepends the label to the interaction FQDN
+    fn format_dns_payload(&self, label: &str) -> String {
+        format!(
+            "{}.{}",
+            label.trim_matches('.'),
+            self.get_interaction_fqdn()
+        )
+    }
+
+    /// Generates a fresh random label and the DNS payload made by prepending
+    /// it to the interaction FQDN (as `<label>.<interaction FQDN>`), returned
+    /// as `(label, payload)`.
+    ///
+    /// The server correlates interactions with any subdomain of the
+    /// interaction FQDN, so polling this client returns the interactions of
+    /// every generated payload. Giving each test case its own payload makes
+    /// it possible to tell their interactions apart by the full ID of the
+    /// logs, without registering again.
+    ///
+    /// The label is shortened to fit within
+    /// [max_payload_label_len](RegisteredClient::max_payload_label_len()).
+    /// Returns None if the client was deregistered or if the interaction
+    /// FQDN leaves no room for a label.
+    pub fn new_payload_label(&self) -> Option<(String, String)> {
+        let label_len = PAYLOAD_LABEL_LENGTH.min(self.max_payload_label_len());
+        if !self.registered || label_len == 0 {
+            return None;
+        }
+
+        let label = Alphanumeric
+            .sample_string(&mut rand::thread_rng(), label_len)
+            .to_ascii_lowercase();
+        let payload = self.format_dns_payload(&label);
+
+        Some((label, payload))
+    }
+
+    /// Gets a matcher that checks whether a hostname belongs to this client's
+    /// interaction FQDN (either the FQDN itself or any subdomain of it).
+    ///
+    /// This is useful for attributing hits from catch-all (`*.<interaction FQDN>`)
+    /// probes. The comparison ignores case and a trailing dot.
+    pub fn wildcard_matcher(&self) -> impl Fn(&str) -> bool {
+        let interaction_fqdn = self.get_interaction_fqdn().to_ascii_lowercase();
+
+        move |hostname| {
+            let hostname = hostname.trim_end_matches('.').to_ascii_lowercase();
+
+            match hostname.strip_suffix(interaction_fqdn.as_str()) {
+                Some(prefix) => prefix.is_empty() || prefix.ends_with('.'),
+                None => false,
+            }
+        }
+    }
+
+    /// Exports the state of this client session.
+    ///
+    /// The returned [SessionState] can be serialized and later passed to
+    /// [with_session](crate::client::ClientBuilder::with_session()) to restore
+    /// the client with the same interaction FQDN without registering again.
+    /// The session of a client that was deregistered with
+    /// [try_deregister](RegisteredClient::try_deregister()) can still be
+    /// exported, but the server no longer knows it, so a restored client
+    /// can't poll it.
+    ///
+    /// If the client was built with
+    /// [deregister_on_drop](crate::client::ClientBuilder::deregister_on_drop())
+    /// set, exporting the session stops this client and its clones from
+    /// deregistering when dropped.
+    pub fn export_session(&self) -> Result<SessionState, CryptoError> {
+        let rsa_key_pem = self.rsa_key.to_pkcs8_pem()?;
+
+        #[cfg(feature = "async-compat")]
+        if let Some(deregister_guard) = &self.deregister_guard {
+            deregister_guard.disarm();
+        }
+
+        Ok(SessionState {
+            rsa_key_pem: Secret::new(rsa_key_pem.as_str().to_owned()),
+            secret_key: self.secret_key.clone(),
+            server: self.server.clone(),
+            sub_domain: self.sub_domain.clone(),
+            correlation_id: self.correlation_id.clone(),
+        })
+    }
+
     /// Deregisters the [RegisteredClient] with the Interactsh server.
     ///
     /// If the deregistration fails, this returns a
     /// [ClientRegistrationError](super::errors::ClientRegistrationError),
-    /// which contains a clone of this client if another try is needed.
+    /// which contains a clone of this client if another try is needed. If the
+    /// client was already deregistered with
+    /// [try_deregister](RegisteredClient::try_deregister()), this returns
+    /// `Ok` without contacting the server.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                otel.kind = "client",
+                peer.service = %self.server,
+                correlation_id = %self.correlation_id,
+            )
+        )
+    )]
     pub async fn deregister(self) -> Result<(), ClientRegistrationError<RegisteredClient>> {
+        if !self.registered {
+            return Ok(());
+        }
+
+        let deregister_result = self.send_deregister().await;
+        if let Err(error) = deregister_result {
+            return Err(error)
+                .context(client_registration_error::ClientRegistration { client: self });
+        }
+
+        Ok(())
+    }
+
+    /// Deregisters the [RegisteredClient] with the Interactsh server without
+    /// consuming it, so the same client can be used to try again if the
+    /// deregistration fails.
+    ///
+    /// Once the deregistration succeeds, [is_registered](RegisteredClient::is_registered())
+    /// returns false, further calls return `Ok` without contacting the server,
+    /// and polling returns a [NotRegistered](ClientPollError::NotRegistered)
+    /// error. Clones of the client made before the deregistration are not
+    /// updated.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                otel.kind = "client",
+                peer.service = %self.server,
+                correlation_id = %self.correlation_id,
+            )
+        )
+    )]
+    pub async fn try_deregister(&mut self) -> Result<(), RegistrationError> {
+        if !self.registered {
+            return Ok(());
+        }
+
+        self.send_deregister().await?;
+        self.registered = false;
+
+        Ok(())
+    }
+
+    /// Checks whether the client is still registered, ie. it has not been
+    /// deregistered with [try_deregister](RegisteredClient::try_deregister()).
+    ///
+    /// This does not contact the server, so it doesn't detect sessions that
+    /// the server dropped on its own.
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+
+    /// Sends the deregister request to the server
+    async fn send_deregister(&self) -> Result<(), RegistrationError> {
         let post_data = DeregisterData {
             correlation_id: self.correlation_id.clone(),
             secret_key: self.secret_key.expose_secret().clone(),
         };
 
-        self.do_registration_request(post_data)
-            .await
-            .context(client_registration_error::ClientRegistration { client: self })?;
+        self.do_registration_request(post_data).await?;
+
+        // The client is no longer registered, so there is nothing left to do on drop
+        #[cfg(feature = "async-compat")]
+        if let Some(deregister_guard) = &self.deregister_guard {
+            deregister_guard.disarm();
+        }
 
         Ok(())
     }
 
+    /// Adds a guard that deregisters the client when the last clone of the
+    /// client is dropped
+    #[cfg(feature = "async-compat")]
+    pub(crate) fn with_deregister_guard(self) -> Self {
+        let deregister_guard = DeregisterGuard::new(&self);
+
+        Self {
+            deregister_guard: Some(Arc::new(deregister_guard)),
+            ..self
+        }
+    }
+
+    /// Polls the server until no new logs are returned (up to `max_iters`
+    /// polls), then deregisters the client.
+    ///
+    /// This is intended for short-lived tools that only need to collect the
+    /// logs buffered on the server before cleaning up. The collected logs are
+    /// always returned, even if a poll or the deregistration fails; any
+    /// errors are returned alongside the logs in the [CollectedLogs].
+    pub async fn collect_and_deregister(self, max_iters: usize) -> CollectedLogs {
+        let (logs, poll_error) = drain_polls(max_iters, || self.poll()).await;
+        let deregister_error = self.deregister().await.err();
+
+        CollectedLogs {
+            logs,
+            poll_error,
+            deregister_error,
+        }
+    }
+
+    /// Polls the server every `poll_period` until `total` has elapsed,
+    /// returning all of the logs collected.
+    ///
+    /// This is intended for one-shot checks that only need the interactions
+    /// seen within a fixed window. The server is polled once more when the
+    /// window ends. If the server rate limits the client, the next poll waits
+    /// for the time it requested instead. If a poll fails for any other
+    /// reason, polling stops and the error is returned along with the logs
+    /// collected before it in the [PolledLogs]. Otherwise, waiting between
+    /// polls behaves the same as
+    /// [wait_for_next_poll](RegisteredClient::wait_for_next_poll()).
+    pub async fn collect_logs(&self, poll_period: Duration, total: Duration) -> PolledLogs {
+        collect_polls_for(
+            total,
+            || self.poll(),
+            |remaining| self.wait_for_next_poll(poll_period.min(remaining)),
+        )
+        .await
+    }
+
+    /// Polls the server every `poll_period` until a parsed log of the
+    /// provided protocol appears, returning the first matching log along with
+    /// the other logs returned by the same poll.
+    ///
+    /// If the protocol is None, the first parsed log of any protocol is
+    /// returned. Returns None if no matching log appears before `timeout`
+    /// elapses. Logs of earlier polls that don't match are skipped, but are
+    /// still recorded in the client's history and passed to its log sinks.
+    /// Rate limiting is handled the same as in
+    /// [collect_logs](RegisteredClient::collect_logs()).
+    pub async fn wait_for_interaction(
+        &self,
+        protocol: Option<LogProtocol>,
+        poll_period: Duration,
+        timeout: Duration,
+    ) -> Result<Option<InteractionMatch>, ClientPollError> {
+        let is_match = |log_entry: &LogEntry| match (log_entry, &protocol) {
+            (LogEntry::ParsedLog(parsed_log), Some(protocol)) => {
+                parsed_log.log_protocol() == *protocol
+            }
+            (LogEntry::ParsedLog(_), None) => true,
+            (LogEntry::RawLog(_), _) => false,
+        };
+
+        poll_until_match(
+            timeout,
+            || self.poll(),
+            |remaining| self.wait_for_next_poll(poll_period.min(remaining)),
+            is_match,
+        )
+        .await
+    }
+
     /// Polls the Interactsh server for any new logs.
+    ///
+    /// If the client was built with
+    /// [retry_on_parse_failure](crate::client::ClientBuilder::retry_on_parse_failure())
+    /// set, the poll is retried once if the server response could not be
+    /// read or parsed.
+    ///
+    /// If a [LogSink](crate::client::LogSink) was added to the client, each
+    /// new log is passed to the sink before the logs are returned. If a sink
+    /// fails and the [SinkErrorPolicy](crate::client::SinkErrorPolicy) is set
+    /// to stop the poll, the logs are returned inside the
+    /// [SinkFailure](crate::errors::ClientPollError::SinkFailure) error.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                otel.kind = "client",
+                peer.service = %self.server,
+                correlation_id = %self.correlation_id,
+                http.status_code = tracing::field::Empty,
+                interactsh.log_count = tracing::field::Empty,
+            )
+        )
+    )]
     pub async fn poll(&self) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
-        let poll_url = format!("https://{}/poll", self.server);
+        let response_body = self
+            .fetch_poll_response_with_retry(&self.correlation_id, &self.secret_key)
+            .await?;
+
+        let logs = self.decrypt_poll_response(response_body)?;
+
+        #[cfg(feature = "tracing")]
+        {
+            let log_count = logs.as_ref().map_or(0, Vec::len);
+            tracing::Span::current().record("interactsh.log_count", log_count);
+            tracing::debug!(log_count, "Polled the Interactsh server");
+        }
+
+        if let Some(logs) = &logs {
+            self.record_polled_logs(logs).await?;
+        }
+
+        Ok(logs)
+    }
+
+    /// Polls the Interactsh server for any new logs, continuing past logs
+    /// that fail to decode or decrypt.
+    ///
+    /// Unlike [poll](RegisteredClient::poll()), which fails if any log in the
+    /// poll response fails to decrypt, this returns the logs that were
+    /// decrypted along with the errors of the logs that weren't. An error is
+    /// still returned if the poll request fails or if the AES key of the poll
+    /// response can't be decrypted. Only the decrypted logs are recorded in
+    /// the client's history and passed to its log sinks.
+    pub async fn poll_lenient(&self) -> Result<Option<LenientLogs>, ClientPollError> {
+        let response_body = self
+            .fetch_poll_response_with_retry(&self.correlation_id, &self.secret_key)
+            .await?;
+
+        let lenient_logs = self.decrypt_poll_response_lenient(response_body)?;
+
+        if let Some(lenient_logs) = &lenient_logs {
+            self.record_polled_logs(&lenient_logs.logs).await?;
+        }
+
+        Ok(lenient_logs)
+    }
+
+    /// Polls the Interactsh server for any new logs, retrying up to
+    /// `max_retries` times if the poll request fails to send.
+    ///
+    /// Only transport-level failures ([PollFailure](ClientPollError::PollFailure))
+    /// are retried. The wait before each retry starts at `base_delay`, doubles
+    /// after every retry, and has random jitter applied. Any other error,
+    /// including an error status returned by the server, is returned
+    /// immediately. If all retries fail, the last error is returned.
+    pub async fn poll_with_retry(
+        &self,
+        max_retries: usize,
+        base_delay: Duration,
+    ) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
+        retry_poll_failures(max_retries, base_delay, || self.poll()).await
+    }
+
+    /// Polls the Interactsh server for any new logs of another session,
+    /// identified by its correlation ID and secret key.
+    ///
+    /// This allows a single client to service several sessions on a server.
+    /// The logs are decrypted with the provided RSA key of the other session,
+    /// or with this client's key if none is provided. The logs are not
+    /// recorded in the client's history or passed to its log sinks, and the
+    /// client's [last_decrypt_duration](RegisteredClient::last_decrypt_duration())
+    /// is left unchanged.
+    pub async fn poll_correlation(
+        &self,
+        correlation_id: &str,
+        secret_key: &Secret<String>,
+        rsa_key: Option<&RSAPrivKey>,
+    ) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
+        let response_body = self
+            .fetch_poll_response_with_retry(correlation_id, secret_key)
+            .await?;
+
+        let data_list = match response_body.data_list {
+            Some(data_list) if !data_list.is_empty() => data_list,
+            _ => return Ok(None),
+        };
+        let rsa_key = rsa_key.unwrap_or(&self.rsa_key);
+
+        let logs = log_decrypt::decrypt_poll_response(
+            &response_body.aes_key,
+            &data_list,
+            rsa_key,
+            self.rsa_padding,
+            self.oaep_hash,
+            self.parse_logs,
+        )?;
+
+        Ok(Some(logs))
+    }
+
+    /// Gets the most recently polled logs retained by the client, from
+    /// oldest to newest.
+    ///
+    /// Logs are only retained if the client was built with a
+    /// [max_history](crate::client::ClientBuilder::max_history()) size.
+    pub fn history(&self) -> Vec<LogEntry> {
+        self.log_history.logs()
+    }
+
+    /// Polls the Interactsh server for any new logs and groups them by protocol.
+    ///
+    /// Raw logs are grouped under `LogProtocol::Other("raw")`.
+    pub async fn poll_grouped(
+        &self,
+    ) -> Result<Option<HashMap<LogProtocol, Vec<LogEntry>>>, ClientPollError> {
+        let logs = self.poll().await?;
+
+        Ok(logs.map(interaction_log::group_by_protocol))
+    }
+
+    /// Polls the Interactsh server for any new logs and groups them by
+    /// timestamp, ordered from oldest to newest.
+    ///
+    /// Raw logs do not have a timestamp and are not included in the returned
+    /// timeline.
+    pub async fn poll_timeline(
+        &self,
+    ) -> Result<Option<BTreeMap<OffsetDateTime, Vec<LogEntry>>>, ClientPollError> {
+        let logs = self.poll().await?;
+
+        Ok(logs.map(interaction_log::group_by_timestamp))
+    }
+
+    /// Waits for the provided poll interval before returning.
+    ///
+    /// If the client was built with a
+    /// [keepalive_ping](crate::client::ClientBuilder::keepalive_ping()) interval,
+    /// a HEAD request is sent to the server on that interval while waiting to
+    /// keep the connection warm. Keepalive request failures are ignored.
+    pub async fn wait_for_next_poll(&self, poll_interval: Duration) {
+        let schedule = keepalive::keepalive_schedule(poll_interval, self.keepalive_interval);
+        let keepalive_count = schedule.len().saturating_sub(1);
+
+        for (index, interval) in schedule.into_iter().enumerate() {
+            keepalive::sleep(interval).await;
+
+            if index < keepalive_count {
+                self.send_keepalive().await;
+            }
+        }
+    }
+
+    /// Waits for the provided poll interval with random jitter applied before
+    /// returning.
+    ///
+    /// The wait is scaled by a random factor between `1 - jitter_fraction` and
+    /// `1 + jitter_fraction` (with the fraction clamped between 0 and 1), which
+    /// is recomputed on every call. This keeps many clients polling on the same
+    /// interval from hitting the server in lockstep. Otherwise this behaves the
+    /// same as [wait_for_next_poll](RegisteredClient::wait_for_next_poll()).
+    pub async fn wait_for_next_poll_with_jitter(
+        &self,
+        poll_interval: Duration,
+        jitter_fraction: f64,
+    ) {
+        let poll_interval = keepalive::jittered_interval(poll_interval, jitter_fraction);
+
+        self.wait_for_next_poll(poll_interval).await;
+    }
+
+    /// Waits for the provided poll interval before returning, unless the cancel
+    /// future resolves first.
+    ///
+    /// Returns true if the full interval elapsed, or false if the wait was
+    /// cancelled. This makes it possible to stop a polling loop on a shutdown
+    /// signal (such as a cancellation token) without deregistering the client.
+    /// Otherwise this behaves the same as
+    /// [wait_for_next_poll](RegisteredClient::wait_for_next_poll()).
+    pub async fn wait_for_next_poll_until<C>(&self, poll_interval: Duration, cancel: C) -> bool
+    where
+        C: Future<Output = ()>,
+    {
+        keepalive::run_until_cancelled(self.wait_for_next_poll(poll_interval), cancel).await
+    }
+
+    /// Gets the time spent decrypting the logs from the most recent poll that
+    /// returned any logs.
+    ///
+    /// This only covers decrypting the AES key and the logs, not the time spent
+    /// sending the poll request. Returns None if no logs have been polled yet.
+    pub fn last_decrypt_duration(&self) -> Option<Duration> {
+        *self
+            .last_decrypt_duration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Gets the most recently decrypted AES key that the server used to
+    /// encrypt the polled logs.
+    ///
+    /// This is only intended as a debugging aid when troubleshooting server
+    /// interoperability, and is only available in debug builds with the
+    /// `test-utils` feature flag enabled.
+    #[cfg(all(feature = "test-utils", debug_assertions))]
+    pub fn last_decrypted_aes_key(&self) -> Option<Secret<Vec<u8>>> {
+        self.last_aes_key.get()
+    }
+
+    /// Sends a poll request to the server for the provided session, retrying
+    /// once if the response can't be read or parsed and the client was built
+    /// with `retry_on_parse_failure` set
+    async fn fetch_poll_response_with_retry(
+        &self,
+        correlation_id: &str,
+        secret_key: &Secret<String>,
+    ) -> Result<PollResponse, ClientPollError> {
+        match self.fetch_poll_response(correlation_id, secret_key).await {
+            Err(ClientPollError::ResponseBodyReadFailed { .. })
+            | Err(ClientPollError::ResponseJsonParseFailed { .. })
+                if self.retry_on_parse_failure =>
+            {
+                self.fetch_poll_response(correlation_id, secret_key).await
+            }
+            poll_result => poll_result,
+        }
+    }
+
+    /// Sends a poll request to the server for the provided session and
+    /// parses the response body
+    async fn fetch_poll_response(
+        &self,
+        correlation_id: &str,
+        secret_key: &Secret<String>,
+    ) -> Result<PollResponse, ClientPollError> {
+        ensure!(self.registered, client_poll_error::NotRegistered);
+
+        let poll_url = http_utils::endpoint_url(
+            &self.server_url,
+            &self.endpoint_paths.poll,
+            self.trailing_slash,
+        );
 
         let mut query_params = SmallVec::<[(String, String); 2]>::new();
-        query_params.push(("id".into(), self.correlation_id.clone()));
-        query_params.push(("secret".into(), self.secret_key.expose_secret().clone()));
+        query_params.push(("id".into(), correlation_id.to_owned()));
+        query_params.push(("secret".into(), secret_key.expose_secret().clone()));
 
         let request_info = HttpRequest::new_get_request(poll_url, query_params);
 
-        let get_response = http_utils::make_http_request(
-            &self.reqwest_client,
-            self.auth_token.as_ref(),
-            request_info,
-        )
-        .await
-        .context(client_poll_error::PollFailure)?;
+        let request_options = RequestOptions {
+            auth_token: self.auth_token.as_ref(),
+            user_agent: self.user_agents.next_user_agent(),
+            custom_headers: &self.custom_headers,
+            timeout: self.poll_timeout,
+            request_customizer: self.request_customizer.as_ref(),
+        };
+
+        let get_response =
+            http_utils::make_http_request(&self.reqwest_client, request_options, request_info)
+                .await
+                .context(client_poll_error::PollFailure)?;
 
         let status = &get_response.status();
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("http.status_code", status.as_u16());
+
+        if *status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = get_response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|header_value| header_value.to_str().ok())
+                .and_then(|header_value| {
+                    http_utils::parse_retry_after(header_value, OffsetDateTime::now_utc())
+                })
+                .unwrap_or(http_utils::DEFAULT_RETRY_AFTER);
+
+            return client_poll_error::RateLimited { retry_after }.fail();
+        }
+
         if !status.is_success() {
             let server_msg = get_response
                 .text()
@@ -100,11 +809,87 @@ impl RegisteredClient {
             return error.fail();
         }
 
-        let response_body = get_response
-            .json::<PollResponse>()
+        let response_text = get_response
+            .text()
             .await
-            .context(client_poll_error::ResponseJsonParseFailed)?;
+            .context(client_poll_error::ResponseBodyReadFailed)?;
+
+        serde_json::from_str::<PollResponse>(&response_text).context(
+            client_poll_error::ResponseJsonParseFailed {
+                body_snippet: http_utils::body_snippet(&response_text),
+            },
+        )
+    }
+
+    /// Sends a HEAD request to the server, ignoring the result
+    async fn send_keepalive(&self) {
+        if !self.registered {
+            return;
+        }
 
+        let keepalive_url = format!("{}/", self.server_url);
+        let request_info = HttpRequest::new_head_request(keepalive_url);
+
+        let request_options = RequestOptions {
+            auth_token: self.auth_token.as_ref(),
+            user_agent: self.user_agents.next_user_agent(),
+            custom_headers: &self.custom_headers,
+            timeout: None,
+            request_customizer: self.request_customizer.as_ref(),
+        };
+
+        let _keepalive_result =
+            http_utils::make_http_request(&self.reqwest_client, request_options, request_info)
+                .await;
+    }
+
+    /// Records newly polled logs in the history and passes them to the log sinks
+    async fn record_polled_logs(&self, logs: &[LogEntry]) -> Result<(), ClientPollError> {
+        self.log_history.record(logs);
+
+        self.log_sinks
+            .dispatch(logs)
+            .await
+            .context(client_poll_error::SinkFailure {
+                logs: logs.to_vec(),
+            })
+    }
+
+    /// Decrypts the logs contained in a poll response from the server
+    fn decrypt_poll_response(
+        &self,
+        response_body: PollResponse,
+    ) -> Result<Option<Vec<LogEntry>>, ClientPollError> {
+        self.decrypt_poll_response_with(response_body, |aes_key, data_list| {
+            log_decrypt::decrypt_logs(aes_key, data_list, self.parse_logs)
+        })
+    }
+
+    /// Decrypts the logs contained in a poll response from the server,
+    /// continuing past logs that fail to decrypt
+    fn decrypt_poll_response_lenient(
+        &self,
+        response_body: PollResponse,
+    ) -> Result<Option<LenientLogs>, ClientPollError> {
+        self.decrypt_poll_response_with(response_body, |aes_key, data_list| {
+            Ok(log_decrypt::decrypt_logs_lenient(
+                aes_key,
+                data_list,
+                self.parse_logs,
+            ))
+        })
+    }
+
+    /// Decrypts the AES key of a poll response from the server, then decrypts
+    /// the logs with the provided function
+    fn decrypt_poll_response_with<T, F>(
+        &self,
+        response_body: PollResponse,
+        decrypt_logs: F,
+    ) -> Result<Option<T>, ClientPollError>
+    where
+        F: FnOnce(&[u8], &[String]) -> Result<T, ClientPollError>,
+    {
         let response_body_data = match response_body.data_list {
             Some(data) => {
                 if data.is_empty() {
@@ -115,58 +900,1051 @@ impl RegisteredClient {
             }
             None => return Ok(None),
         };
-        let aes_key_decoded = general_purpose::STANDARD
-            .decode(&response_body.aes_key)
-            .context(client_poll_error::Base64DecodeFailed)?;
-
-        let mut results = Vec::new();
-        for data in response_body_data.iter() {
-            let data_decoded = general_purpose::STANDARD
-                .decode(data)
-                .context(client_poll_error::Base64DecodeFailed)?;
-            let decrypted_data = self.decrypt_data(&aes_key_decoded, &data_decoded)?;
-
-            let log_entry = if self.parse_logs {
-                LogEntry::try_parse_log(decrypted_data.as_str())
-            } else {
-                LogEntry::return_raw_log(decrypted_data.as_str())
-            };
 
-            results.push(log_entry);
-        }
+        let decrypt_start = Instant::now();
+
+        let aes_key = log_decrypt::decrypt_aes_key(
+            &self.rsa_key,
+            &response_body.aes_key,
+            self.rsa_padding,
+            self.oaep_hash,
+        )?;
+
+        #[cfg(all(feature = "test-utils", debug_assertions))]
+        self.last_aes_key.set(&aes_key);
+
+        let results = decrypt_logs(&aes_key, &response_body_data)?;
+
+        let mut last_decrypt_duration = self
+            .last_decrypt_duration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *last_decrypt_duration = Some(decrypt_start.elapsed());
 
         Ok(Some(results))
     }
+}
 
-    fn decrypt_data(
-        &self,
-        aes_key: &[u8],
-        encrypted_data: &[u8],
-    ) -> Result<String, ClientPollError> {
-        let aes_plain_key = self
-            .rsa_key
-            .decrypt_data(aes_key)
-            .context(client_poll_error::AesKeyDecryptFailed)?;
+/// Repeatedly runs the poll function until it returns no logs, returns an
+/// error, or has been run `max_iters` times, accumulating the returned logs
+async fn drain_polls<F, Fut>(
+    max_iters: usize,
+    mut poll: F,
+) -> (Vec<LogEntry>, Option<ClientPollError>)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<Vec<LogEntry>>, ClientPollError>>,
+{
+    let mut collected_logs = Vec::new();
+
+    for _ in 0..max_iters {
+        match poll().await {
+            Ok(Some(logs)) => collected_logs.extend(logs),
+            Ok(None) => break,
+            Err(error) => return (collected_logs, Some(error)),
+        }
+    }
+
+    (collected_logs, None)
+}
 
-        let decrypted_data = aes::decrypt_data(&aes_plain_key, encrypted_data)
-            .context(client_poll_error::DataDecryptFailed)?;
+/// Repeatedly runs the poll function until `total` has elapsed, accumulating
+/// the returned logs. The wait function is passed the time remaining and is
+/// run between polls. If a poll fails, the logs collected before it are
+/// returned along with the error.
+async fn collect_polls_for<P, PFut, W, WFut>(total: Duration, poll: P, wait: W) -> PolledLogs
+where
+    P: FnMut() -> PFut,
+    PFut: Future<Output = Result<Option<Vec<LogEntry>>, ClientPollError>>,
+    W: FnMut(Duration) -> WFut,
+    WFut: Future<Output = ()>,
+{
+    let mut collected_logs = Vec::new();
 
-        let decrypted_string = String::from_utf8_lossy(&decrypted_data);
+    let poll_result = poll_until(total, poll, wait, |logs| {
+        collected_logs.extend(logs);
+        None::<()>
+    })
+    .await;
 
-        Ok(decrypted_string.into())
+    PolledLogs {
+        logs: collected_logs,
+        poll_error: poll_result.err(),
     }
 }
 
+/// Repeatedly runs the poll function until it returns a log accepted by the
+/// match function or `timeout` has elapsed. The wait function is passed the
+/// time remaining and is run between polls.
+async fn poll_until_match<P, PFut, W, WFut, M>(
+    timeout: Duration,
+    poll: P,
+    wait: W,
+    is_match: M,
+) -> Result<Option<InteractionMatch>, ClientPollError>
+where
+    P: FnMut() -> PFut,
+    PFut: Future<Output = Result<Option<Vec<LogEntry>>, ClientPollError>>,
+    W: FnMut(Duration) -> WFut,
+    WFut: Future<Output = ()>,
+    M: Fn(&LogEntry) -> bool,
+{
+    poll_until(timeout, poll, wait, |mut logs| {
+        let match_index = logs.iter().position(&is_match)?;
+        let log = logs.remove(match_index);
+
+        Some(InteractionMatch {
+            log,
+            other_logs: logs,
+        })
+    })
+    .await
+}
+
+/// Repeatedly runs the poll function until the logs handler returns a value
+/// or `timeout` has elapsed, waiting between polls. The server is polled once
+/// more when the timeout elapses.
+///
+/// The logs of each poll are passed to the logs handler. The wait function is
+/// passed the time remaining and is run between polls, unless the server rate
+/// limited the client, in which case the time it requested is waited instead.
+/// Returns None if the timeout elapsed, or the first error that was not rate
+/// limiting.
+async fn poll_until<P, PFut, W, WFut, H, T>(
+    timeout: Duration,
+    mut poll: P,
+    mut wait: W,
+    mut handle_logs: H,
+) -> Result<Option<T>, ClientPollError>
+where
+    P: FnMut() -> PFut,
+    PFut: Future<Output = Result<Option<Vec<LogEntry>>, ClientPollError>>,
+    W: FnMut(Duration) -> WFut,
+    WFut: Future<Output = ()>,
+    H: FnMut(Vec<LogEntry>) -> Option<T>,
+{
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let rate_limit = match poll().await {
+            Err(ClientPollError::RateLimited { retry_after }) => Some(retry_after),
+            poll_result => {
+                let logs = poll_result?.unwrap_or_default();
+                if let Some(handled) = handle_logs(logs) {
+                    return Ok(Some(handled));
+                }
+                None
+            }
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+
+        wait_or_back_off(rate_limit, remaining, &mut wait).await;
+    }
+}
+
+/// Waits for the next poll, or sleeps for the time requested by the server
+/// if the last poll was rate limited. The sleep is capped at `remaining`.
+async fn wait_or_back_off<W, WFut>(rate_limit: Option<Duration>, remaining: Duration, wait: &mut W)
+where
+    W: FnMut(Duration) -> WFut,
+    WFut: Future<Output = ()>,
+{
+    match rate_limit {
+        Some(retry_after) => keepalive::sleep(retry_after.min(remaining)).await,
+        None => wait(remaining).await,
+    }
+}
+
+/// Runs the poll function, retrying up to `max_retries` times with
+/// exponential backoff if it returns a [PollFailure](ClientPollError::PollFailure)
+async fn retry_poll_failures<F, Fut>(
+    max_retries: usize,
+    base_delay: Duration,
+    mut poll: F,
+) -> Result<Option<Vec<LogEntry>>, ClientPollError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<Vec<LogEntry>>, ClientPollError>>,
+{
+    let mut retries = 0;
+
+    loop {
+        match poll().await {
+            Err(ClientPollError::PollFailure { .. }) if retries < max_retries => {
+                keepalive::sleep(backoff_delay(base_delay, retries)).await;
+                retries += 1;
+            }
+            poll_result => return poll_result,
+        }
+    }
+}
+
+/// Calculates the wait before the provided retry, doubling the base delay
+/// for each previous retry and applying random jitter of up to half the delay
+fn backoff_delay(base_delay: Duration, retry: usize) -> Duration {
+    let exponent = u32::try_from(retry)
+        .unwrap_or(MAX_BACKOFF_EXPONENT)
+        .min(MAX_BACKOFF_EXPONENT);
+    let delay = base_delay.saturating_mul(2u32.pow(exponent));
+    let jitter = delay.mul_f64(rand::thread_rng().gen_range(0.0..=0.5));
+
+    delay.saturating_sub(jitter)
+}
+
+/// Calculates the maximum length of a label that can be prepended to
+/// the provided interaction FQDN
+fn payload_label_budget(interaction_fqdn: &str) -> usize {
+    // Account for the dot between the label and the interaction FQDN
+    let remaining_len = MAX_DOMAIN_NAME_LENGTH.saturating_sub(interaction_fqdn.len() + 1);
+
+    remaining_len.min(MAX_DNS_LABEL_LENGTH)
+}
+
 impl Client for RegisteredClient {
     fn get_registration_url(&self) -> String {
-        format!("https://{}/deregister", &self.server)
+        http_utils::endpoint_url(
+            &self.server_url,
+            &self.endpoint_paths.deregister,
+            self.trailing_slash,
+        )
     }
 
     fn get_reqwest_client(&self) -> &reqwest::Client {
         &self.reqwest_client
     }
 
-    fn get_auth_token(&self) -> Option<&Secret<String>> {
+    fn get_auth_token(&self) -> Option<&AuthToken> {
         self.auth_token.as_ref()
     }
+
+    fn get_user_agents(&self) -> &UserAgentRotation {
+        &self.user_agents
+    }
+
+    fn get_custom_headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.custom_headers
+    }
+
+    fn get_request_customizer(&self) -> Option<&RequestCustomizer> {
+        self.request_customizer.as_ref()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::log_decrypt::test_utils::encrypt_logs;
+    use crate::client::mock_server::{MockResponse, MockServer};
+    use crate::client::{ClientBuilder, LogSink, SinkErrorPolicy};
+    use crate::errors::SinkError;
+
+    const TEST_LOG: &str =
+        r#"{"protocol":"smb","raw-request":"request","timestamp":"2023-01-01T00:00:00Z"}"#;
+
+    fn build_test_client() -> RegisteredClient {
+        ClientBuilder::default()
+            .build()
+            .expect("Default build failed")
+            .into_registered()
+            .expect("Failed to convert the client")
+    }
+
+    fn encrypted_poll_response(client: &RegisteredClient, logs: &[&str]) -> PollResponse {
+        let pub_key = client
+            .rsa_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+        let (aes_key, data_list) = encrypt_logs(&pub_key, logs);
+
+        PollResponse {
+            aes_key,
+            data_list: Some(data_list),
+        }
+    }
+
+    /// Builds the JSON body of a poll response containing the logs
+    fn poll_response_body(client: &RegisteredClient, logs: &[&str]) -> String {
+        let poll_response = encrypted_poll_response(client, logs);
+
+        serde_json::json!({
+            "aes_key": poll_response.aes_key,
+            "data": poll_response.data_list,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn payload_label_budget_is_limited_by_label_length() {
+        let client = build_test_client();
+
+        assert_eq!(client.max_payload_label_len(), MAX_DNS_LABEL_LENGTH);
+    }
+
+    #[test]
+    fn payload_url_and_dns_payload_use_interaction_fqdn() {
+        let client = build_test_client();
+        let interaction_fqdn = client.get_interaction_fqdn();
+
+        assert_eq!(
+            client.build_payload_url("https", "//path?x=1"),
+            Some(format!("https://{}/path?x=1", interaction_fqdn))
+        );
+        assert_eq!(
+            client.build_dns_payload(".test1."),
+            Some(format!("test1.{}", interaction_fqdn))
+        );
+    }
+
+    #[test]
+    fn new_payload_labels_are_unique_subdomains() {
+        let client = build_test_client();
+        let matcher = client.wildcard_matcher();
+
+        let (first_label, first_payload) = client
+            .new_payload_label()
+            .expect("No payload label was generated");
+        let (second_label, second_payload) = client
+            .new_payload_label()
+            .expect("No payload label was generated");
+
+        assert_ne!(first_label, second_label);
+        assert!(matcher(&first_payload));
+        assert!(matcher(&second_payload));
+        assert_eq!(first_label.len(), PAYLOAD_LABEL_LENGTH);
+        assert_eq!(
+            first_payload,
+            format!("{}.{}", first_label, client.get_interaction_fqdn())
+        );
+    }
+
+    #[test]
+    fn new_payload_label_fits_the_label_budget() {
+        let mut client = build_test_client();
+        // Leave room for a 3 character label in the 253 character limit
+        client.server = "b".repeat(MAX_DOMAIN_NAME_LENGTH - client.sub_domain.len() - 5);
+
+        let (label, payload) = client
+            .new_payload_label()
+            .expect("No payload label was generated");
+
+        assert_eq!(label.len(), 3);
+        assert_eq!(payload.len(), MAX_DOMAIN_NAME_LENGTH);
+
+        client.server.push_str("bbb");
+        assert!(client.new_payload_label().is_none());
+    }
+
+    #[test]
+    fn payload_label_budget_is_limited_by_domain_name_length() {
+        // 33 character subdomain + 1 dot + 200 character server
+        let interaction_fqdn = format!("{}.{}", "a".repeat(33), "b".repeat(200));
+
+        assert_eq!(payload_label_budget(&interaction_fqdn), 18);
+    }
+
+    #[test]
+    fn payload_label_budget_is_zero_when_fqdn_is_too_long() {
+        let interaction_fqdn = "a".repeat(MAX_DOMAIN_NAME_LENGTH);
+
+        assert_eq!(payload_label_budget(&interaction_fqdn), 0);
+    }
+
+    #[tokio::test]
+    async fn collect_and_deregister_drains_buffered_logs_then_deregisters() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        let correlation_id = client.get_correlation_id().to_owned();
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG, TEST_LOG])),
+        );
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG])),
+        );
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "", "data": []}"#));
+        server.queue_response("/deregister", MockResponse::ok("deregistration successful"));
+
+        let collected_logs = client.collect_and_deregister(10).await;
+
+        assert_eq!(collected_logs.logs.len(), 3);
+        assert!(collected_logs.poll_error.is_none());
+        assert!(collected_logs.deregister_error.is_none());
+        assert_eq!(server.requests_to("/poll").len(), 3);
+        let deregister_requests = server.requests_to("/deregister");
+        assert_eq!(deregister_requests.len(), 1);
+        assert_eq!(
+            deregister_requests[0].json_body()["correlation-id"],
+            correlation_id
+        );
+    }
+
+    #[tokio::test]
+    async fn collect_and_deregister_returns_logs_when_deregister_fails() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG])),
+        );
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "", "data": []}"#));
+        server.queue_response("/deregister", MockResponse::status(500, "server error"));
+
+        let collected_logs = client.collect_and_deregister(10).await;
+
+        assert_eq!(collected_logs.logs.len(), 1);
+        let deregister_error = collected_logs
+            .deregister_error
+            .expect("Deregister error was not returned");
+        assert_eq!(deregister_error.status_code(), Some(500));
+    }
+
+    #[tokio::test]
+    async fn keepalive_requests_are_sent_between_polls() {
+        let server = MockServer::start();
+        server.queue_response("/", MockResponse::ok(""));
+        let client = server
+            .client_builder()
+            .keepalive_ping(Duration::from_millis(20))
+            .build()
+            .expect("Build with keepalive_ping failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        client.wait_for_next_poll(Duration::from_millis(70)).await;
+
+        let keepalive_requests = server.requests_to("/");
+        assert_eq!(keepalive_requests.len(), 3);
+        assert!(keepalive_requests
+            .iter()
+            .all(|request| request.method == "HEAD"));
+    }
+
+    #[tokio::test]
+    async fn no_keepalive_requests_are_sent_by_default() {
+        let server = MockServer::start();
+        server.queue_response("/", MockResponse::ok(""));
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        client.wait_for_next_poll(Duration::from_millis(50)).await;
+
+        assert!(server.requests_to("/").is_empty());
+    }
+
+    #[tokio::test]
+    async fn poll_is_retried_after_a_truncated_response() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .retry_on_parse_failure(true)
+            .build()
+            .expect("Build with retry_on_parse_failure failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "trunc"#));
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG])),
+        );
+
+        let logs = client
+            .poll()
+            .await
+            .expect("Poll was not retried after the truncated response")
+            .expect("No logs returned");
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(server.requests_to("/poll").len(), 2);
+    }
+
+    struct FailingSink;
+
+    #[async_trait::async_trait]
+    impl LogSink for FailingSink {
+        async fn consume(&self, _entry: &LogEntry) -> Result<(), SinkError> {
+            Err(SinkError::new("sink failure"))
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_returns_logs_when_a_sink_fails_by_default() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .with_sink(Arc::new(FailingSink))
+            .build()
+            .expect("Build with a log sink failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG])),
+        );
+
+        let logs = client
+            .poll()
+            .await
+            .expect("Sink error was not ignored")
+            .expect("No logs returned");
+
+        assert_eq!(logs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_poll_sink_error_holds_the_polled_logs() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .with_sink(Arc::new(FailingSink))
+            .sink_error_policy(SinkErrorPolicy::StopPoll)
+            .build()
+            .expect("Build with a log sink failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&client, &[TEST_LOG])),
+        );
+
+        let poll_error = client.poll().await.expect_err("Sink error was not returned");
+
+        match poll_error {
+            ClientPollError::SinkFailure { logs, .. } => assert_eq!(logs.len(), 1),
+            other_error => panic!("Unexpected poll error: {other_error}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_truncated_responses_return_the_body_snippet() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .retry_on_parse_failure(true)
+            .build()
+            .expect("Build with retry_on_parse_failure failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "trunc"#));
+
+        let poll_error = client
+            .poll()
+            .await
+            .expect_err("Poll of a truncated response succeeded");
+
+        match poll_error {
+            ClientPollError::ResponseJsonParseFailed { body_snippet, .. } => {
+                assert!(body_snippet.contains("trunc"));
+            }
+            poll_error => panic!("Unexpected poll error: {:?}", poll_error),
+        }
+        assert_eq!(server.requests_to("/poll").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn truncated_response_is_not_retried_by_default() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        server.queue_response("/poll", MockResponse::ok(r#"{"aes_key": "trunc"#));
+
+        assert!(client.poll().await.is_err());
+        assert_eq!(server.requests_to("/poll").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_try_deregister_keeps_the_client_registered() {
+        let mut client = ClientBuilder::new()
+            .with_server("127.0.0.1".into())
+            .with_server_port(9)
+            .use_tls(false)
+            .with_rsa_key_size(2048)
+            .build()
+            .expect("Build with a closed port failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        assert!(client.try_deregister().await.is_err());
+        assert!(client.is_registered());
+    }
+
+    #[tokio::test]
+    async fn try_deregister_marks_the_client_deregistered() {
+        let server = MockServer::start();
+        server.queue_response("/deregister", MockResponse::ok("deregistration successful"));
+        let mut client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        client
+            .try_deregister()
+            .await
+            .expect("Deregistration with the mock server failed");
+
+        assert!(!client.is_registered());
+        assert_eq!(client.build_payload_url("https", "/"), None);
+        assert_eq!(client.build_dns_payload("test1"), None);
+        let deregister_requests = server.requests_to("/deregister");
+        assert_eq!(deregister_requests.len(), 1);
+        assert_eq!(
+            deregister_requests[0].json_body()["correlation-id"],
+            client.get_correlation_id()
+        );
+
+        // A deregistered client doesn't contact the server again
+        assert!(matches!(
+            client.poll().await,
+            Err(ClientPollError::NotRegistered { .. })
+        ));
+        client
+            .try_deregister()
+            .await
+            .expect("Repeated try_deregister failed");
+        client
+            .deregister()
+            .await
+            .expect("Deregister after try_deregister failed");
+        assert!(server.requests_to("/poll").is_empty());
+        assert_eq!(server.requests_to("/deregister").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn drain_polls_collects_until_no_logs() {
+        let mut buffered_polls = vec![
+            Some(vec![LogEntry::return_raw_log("first log")]),
+            Some(vec![
+                LogEntry::return_raw_log("second log"),
+                LogEntry::return_raw_log("third log"),
+            ]),
+            None,
+            Some(vec![LogEntry::return_raw_log("unreached log")]),
+        ]
+        .into_iter();
+        let mut poll_count = 0;
+
+        let (logs, poll_error) = drain_polls(10, || {
+            poll_count += 1;
+            let poll_result = buffered_polls.next().flatten();
+            async move { Ok(poll_result) }
+        })
+        .await;
+
+        assert_eq!(logs.len(), 3);
+        assert_eq!(poll_count, 3);
+        assert!(poll_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn drain_polls_stops_at_max_iters() {
+        let mut poll_count = 0;
+
+        let (logs, poll_error) = drain_polls(4, || {
+            poll_count += 1;
+            async { Ok(Some(vec![LogEntry::return_raw_log("log")])) }
+        })
+        .await;
+
+        assert_eq!(logs.len(), 4);
+        assert_eq!(poll_count, 4);
+        assert!(poll_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn collect_polls_for_polls_until_total_elapses() {
+        let mut poll_count = 0;
+
+        let polled_logs = collect_polls_for(
+            Duration::from_millis(30),
+            || {
+                poll_count += 1;
+                async { Ok(Some(vec![LogEntry::return_raw_log("log")])) }
+            },
+            |remaining| keepalive::sleep(remaining.min(Duration::from_millis(5))),
+        )
+        .await;
+
+        assert!(polled_logs.poll_error.is_none());
+        assert!(poll_count > 1);
+        assert_eq!(polled_logs.logs.len(), poll_count);
+    }
+
+    #[tokio::test]
+    async fn collect_polls_for_returns_poll_error_with_collected_logs() {
+        let mut poll_count = 0;
+
+        let polled_logs = collect_polls_for(
+            Duration::from_secs(60),
+            || {
+                poll_count += 1;
+                let poll_result = if poll_count < 3 {
+                    Ok(Some(vec![LogEntry::return_raw_log("log")]))
+                } else {
+                    Err(poll_failure())
+                };
+                async move { poll_result }
+            },
+            |_| async {},
+        )
+        .await;
+
+        assert_eq!(poll_count, 3);
+        assert_eq!(polled_logs.logs.len(), 2);
+        assert!(matches!(
+            polled_logs.poll_error,
+            Some(ClientPollError::PollFailure { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn collect_polls_for_backs_off_when_rate_limited() {
+        let mut poll_count = 0;
+        let mut wait_count = 0;
+
+        let polled_logs = collect_polls_for(
+            Duration::from_millis(30),
+            || {
+                poll_count += 1;
+                let poll_result = if poll_count == 1 {
+                    client_poll_error::RateLimited {
+                        retry_after: Duration::from_secs(60),
+                    }
+                    .fail()
+                } else {
+                    Ok(Some(vec![LogEntry::return_raw_log("log")]))
+                };
+                async move { poll_result }
+            },
+            |_| {
+                wait_count += 1;
+                async {}
+            },
+        )
+        .await;
+
+        assert!(polled_logs.poll_error.is_none());
+        assert_eq!(poll_count, 2);
+        assert_eq!(wait_count, 0);
+        assert_eq!(polled_logs.logs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn poll_until_match_returns_first_matching_log() {
+        let mut buffered_polls = vec![
+            None,
+            Some(vec![LogEntry::return_raw_log("first log")]),
+            Some(vec![
+                LogEntry::return_raw_log("second log"),
+                LogEntry::return_raw_log("match"),
+                LogEntry::return_raw_log("third log"),
+            ]),
+        ]
+        .into_iter();
+
+        let interaction_match = poll_until_match(
+            Duration::from_secs(60),
+            || {
+                let poll_result = buffered_polls.next().flatten();
+                async move { Ok(poll_result) }
+            },
+            |_| async {},
+            |log_entry| log_entry.same_interaction(&LogEntry::return_raw_log("match")),
+        )
+        .await
+        .expect("Polling failed")
+        .expect("No matching log was returned");
+
+        assert!(interaction_match
+            .log
+            .same_interaction(&LogEntry::return_raw_log("match")));
+        assert_eq!(interaction_match.other_logs.len(), 2);
+        assert!(interaction_match.other_logs[1]
+            .same_interaction(&LogEntry::return_raw_log("third log")));
+    }
+
+    #[tokio::test]
+    async fn poll_until_match_returns_none_after_timeout() {
+        let mut poll_count = 0;
+
+        let interaction_match = poll_until_match(
+            Duration::from_millis(20),
+            || {
+                poll_count += 1;
+                async { Ok(Some(vec![LogEntry::return_raw_log("log")])) }
+            },
+            |remaining| keepalive::sleep(remaining.min(Duration::from_millis(5))),
+            |_| false,
+        )
+        .await
+        .expect("Polling failed");
+
+        assert!(interaction_match.is_none());
+        assert!(poll_count > 1);
+    }
+
+    fn poll_failure() -> ClientPollError {
+        let source = reqwest::Client::new()
+            .get("not a url")
+            .build()
+            .expect_err("Invalid URL built successfully");
+
+        ClientPollError::PollFailure { source }
+    }
+
+    #[tokio::test]
+    async fn retry_poll_failures_retries_until_success() {
+        let mut poll_count = 0;
+
+        let poll_result = retry_poll_failures(3, Duration::ZERO, || {
+            poll_count += 1;
+            let poll_result = if poll_count < 3 {
+                Err(poll_failure())
+            } else {
+                Ok(Some(vec![LogEntry::return_raw_log("log")]))
+            };
+            async move { poll_result }
+        })
+        .await;
+
+        assert_eq!(poll_count, 3);
+        assert!(matches!(poll_result, Ok(Some(logs)) if logs.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn retry_poll_failures_returns_last_error_after_max_retries() {
+        let mut poll_count = 0;
+
+        let poll_result = retry_poll_failures(2, Duration::ZERO, || {
+            poll_count += 1;
+            async { Err(poll_failure()) }
+        })
+        .await;
+
+        assert_eq!(poll_count, 3);
+        assert!(matches!(
+            poll_result,
+            Err(ClientPollError::PollFailure { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_poll_failures_does_not_retry_error_status() {
+        let mut poll_count = 0;
+
+        let poll_result = retry_poll_failures(3, Duration::ZERO, || {
+            poll_count += 1;
+            let error = client_poll_error::PollErrorStatus {
+                server_msg: "bad request",
+                status_code: 400u16,
+            };
+            async move { Err(error.build()) }
+        })
+        .await;
+
+        assert_eq!(poll_count, 1);
+        assert!(matches!(
+            poll_result,
+            Err(ClientPollError::PollErrorStatus {
+                status_code: 400,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn backoff_delay_doubles_with_jitter() {
+        let base_delay = Duration::from_millis(100);
+
+        for retry in 0..4 {
+            let full_delay = base_delay * 2u32.pow(retry as u32);
+            let delay = backoff_delay(base_delay, retry);
+
+            assert!(delay <= full_delay);
+            assert!(delay >= full_delay / 2);
+        }
+
+        let capped_delay = Duration::from_secs(2u64.pow(MAX_BACKOFF_EXPONENT));
+        assert!(backoff_delay(Duration::from_secs(1), usize::MAX) <= capped_delay);
+    }
+
+    #[test]
+    fn correlation_id_and_subdomain_make_up_the_interaction_fqdn() {
+        let client = build_test_client();
+
+        assert!(client
+            .get_interaction_fqdn()
+            .starts_with(&format!("{}.", client.get_subdomain())));
+        assert!(client
+            .get_subdomain()
+            .starts_with(client.get_correlation_id()));
+    }
+
+    #[test]
+    fn typed_interaction_fqdn_matches_interaction_fqdn() {
+        let client = build_test_client();
+
+        let interaction_fqdn = client.get_typed_interaction_fqdn();
+
+        assert_eq!(interaction_fqdn.fqdn(), client.get_interaction_fqdn());
+        assert_eq!(interaction_fqdn.subdomain(), client.get_subdomain());
+    }
+
+    #[test]
+    fn wildcard_matcher_accepts_subdomains_of_the_interaction_fqdn() {
+        let client = build_test_client();
+        let interaction_fqdn = client.get_interaction_fqdn();
+        let matcher = client.wildcard_matcher();
+
+        assert!(matcher(&interaction_fqdn));
+        assert!(matcher(&format!("foo.bar.{}", interaction_fqdn)));
+        let uppercase_fqdn = interaction_fqdn.to_uppercase();
+        assert!(matcher(&format!("FOO.{}.", uppercase_fqdn)));
+    }
+
+    #[test]
+    fn wildcard_matcher_rejects_unrelated_hosts() {
+        let client = build_test_client();
+        let interaction_fqdn = client.get_interaction_fqdn();
+        let matcher = client.wildcard_matcher();
+
+        assert!(!matcher("example.com"));
+        assert!(!matcher(&format!("foo{}", interaction_fqdn)));
+        assert!(!matcher(&format!("{}.example.com", interaction_fqdn)));
+    }
+
+    #[test]
+    fn empty_poll_response_returns_none() {
+        let client = build_test_client();
+        let poll_response = PollResponse {
+            aes_key: String::new(),
+            data_list: Some(Vec::new()),
+        };
+
+        let logs = client
+            .decrypt_poll_response(poll_response)
+            .expect("Failed to decrypt an empty poll response");
+
+        assert!(logs.is_none());
+    }
+
+    #[test]
+    fn poll_response_decrypts_all_logs() {
+        let client = build_test_client();
+        let poll_response = encrypted_poll_response(&client, &["first log", "second log"]);
+
+        let logs = client
+            .decrypt_poll_response(poll_response)
+            .expect("Failed to decrypt the poll response")
+            .expect("No logs returned");
+
+        assert_eq!(logs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn poll_correlation_decrypts_with_the_other_session_key() {
+        let server = MockServer::start();
+        let client = server
+            .client_builder()
+            .build()
+            .expect("Build failed")
+            .into_registered()
+            .expect("Failed to convert the client");
+        let other_client = build_test_client();
+        server.queue_response(
+            "/poll",
+            MockResponse::ok(poll_response_body(&other_client, &["other log"])),
+        );
+
+        let logs = client
+            .poll_correlation(
+                other_client.get_correlation_id(),
+                &other_client.secret_key,
+                Some(&other_client.rsa_key),
+            )
+            .await
+            .expect("Poll of the other session failed")
+            .expect("No logs returned");
+
+        assert_eq!(logs.len(), 1);
+        assert!(client.history().is_empty());
+        assert!(client.last_decrypt_duration().is_none());
+    }
+
+    #[test]
+    fn restored_session_decrypts_logs() {
+        let client = build_test_client();
+        let session = client
+            .export_session()
+            .expect("Failed to export the session");
+        let restored_client = ClientBuilder::default()
+            .parse_logs(false)
+            .with_session(session)
+            .expect("Failed to restore the session");
+
+        let poll_response = encrypted_poll_response(&client, &["log"]);
+        let logs = restored_client
+            .decrypt_poll_response(poll_response)
+            .expect("Failed to decrypt the poll response")
+            .expect("No logs returned");
+
+        match &logs[..] {
+            [LogEntry::RawLog(raw_log)] => assert_eq!(raw_log.log_entry, "log"),
+            _ => panic!("Expected a single raw log"),
+        }
+    }
+
+    #[test]
+    fn last_decrypt_duration_is_populated_after_decrypt() {
+        let client = build_test_client();
+        assert!(client.last_decrypt_duration().is_none());
+
+        let poll_response = encrypted_poll_response(&client, &["log"]);
+        let _logs = client
+            .decrypt_poll_response(poll_response)
+            .expect("Failed to decrypt the poll response");
+
+        assert!(client.last_decrypt_duration().is_some());
+    }
+
+    #[test]
+    #[cfg(all(feature = "test-utils", debug_assertions))]
+    fn last_decrypted_aes_key_is_populated_after_decrypt() {
+        let client = build_test_client();
+        assert!(client.last_decrypted_aes_key().is_none());
+
+        let poll_response = encrypted_poll_response(&client, &["log"]);
+        let _logs = client
+            .decrypt_poll_response(poll_response)
+            .expect("Failed to decrypt the poll response");
+
+        let aes_key = client
+            .last_decrypted_aes_key()
+            .expect("AES key was not stored");
+        assert_eq!(aes_key.expose_secret().len(), 32);
+    }
 }
@@ -0,0 +1,129 @@
+//! Defines the session state that can be exported from a
+//! [RegisteredClient](crate::client::RegisteredClient) and used to restore the
+//! client later without registering with the server again.
+
+use secrecy::Secret;
+use serde::{Deserialize, Serialize};
+
+
+/// The state of a registered client session
+///
+/// This contains everything needed to restore a
+/// [RegisteredClient](crate::client::RegisteredClient) with the same interaction FQDN
+/// (see [export_session](crate::client::RegisteredClient::export_session()) and
+/// [with_session](crate::client::ClientBuilder::with_session())), and can be
+/// serialized to persist the session across process restarts.
+///
+/// The RSA private key and secret key are not shown in the Debug output, but
+/// are included in plain text when serialized, so the serialized session
+/// should be stored securely.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    #[serde(with = "secret_string")]
+    pub(crate) rsa_key_pem: Secret<String>,
+    #[serde(with = "secret_string")]
+    pub(crate) secret_key: Secret<String>,
+    pub(crate) server: String,
+    pub(crate) sub_domain: String,
+    pub(crate) correlation_id: String,
+}
+
+impl SessionState {
+    /// Gets the Interactsh server of the session
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Gets the interaction FQDN of the session
+    pub fn interaction_fqdn(&self) -> String {
+        format!("{}.{}", self.sub_domain, self.server)
+    }
+}
+
+
+mod secret_string {
+    use secrecy::{ExposeSecret, Secret};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        secret: &Secret<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(secret.expose_secret())
+    }
+
+    pub fn deserialize<'a, D: Deserializer<'a>>(
+        deserializer: D,
+    ) -> Result<Secret<String>, D::Error> {
+        let secret = String::deserialize(deserializer)?;
+
+        Ok(Secret::new(secret))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use secrecy::ExposeSecret;
+
+    use super::*;
+    use crate::client::ClientBuilder;
+
+    fn build_test_session() -> SessionState {
+        ClientBuilder::default()
+            .build()
+            .expect("Default build failed")
+            .into_registered()
+            .expect("Failed to convert the client")
+            .export_session()
+            .expect("Failed to export the session")
+    }
+
+    #[test]
+    fn session_state_round_trips_through_json() {
+        let session = build_test_session();
+
+        let session_json =
+            serde_json::to_string(&session).expect("Failed to serialize the session");
+        let restored_session: SessionState =
+            serde_json::from_str(&session_json).expect("Failed to deserialize the session");
+
+        assert_eq!(
+            restored_session.rsa_key_pem.expose_secret(),
+            session.rsa_key_pem.expose_secret()
+        );
+        assert_eq!(
+            restored_session.secret_key.expose_secret(),
+            session.secret_key.expose_secret()
+        );
+        assert_eq!(
+            restored_session.interaction_fqdn(),
+            session.interaction_fqdn()
+        );
+        assert_eq!(restored_session.correlation_id, session.correlation_id);
+    }
+
+    #[test]
+    fn session_state_debug_does_not_leak_secrets() {
+        let session = build_test_session();
+
+        let session_debug = format!("{:?}", session);
+
+        assert!(!session_debug.contains(session.secret_key.expose_secret().as_str()));
+        assert!(!session_debug.contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    fn restored_client_keeps_the_interaction_fqdn() {
+        let session = build_test_session();
+
+        let restored_client = ClientBuilder::default()
+            .with_session(session.clone())
+            .expect("Failed to restore the session");
+
+        assert_eq!(
+            restored_client.get_interaction_fqdn(),
+            session.interaction_fqdn()
+        );
+    }
+}
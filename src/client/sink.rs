@@ -0,0 +1,171 @@
+//! Defines the [LogSink] trait, which can be used to forward every log
+//! polled by a [RegisteredClient](crate::client::RegisteredClient) to a
+//! custom destination.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use super::errors::SinkError;
+use crate::interaction_log::LogEntry;
+
+
+/// A destination that receives every log polled by a client
+///
+/// Sinks are added to a client using
+/// [with_sink](crate::client::ClientBuilder::with_sink()), and are called
+/// with each log entry during [poll](crate::client::RegisteredClient::poll())
+/// before the logs are returned to the caller.
+#[async_trait::async_trait]
+pub trait LogSink: Send + Sync {
+    /// Consumes a single log entry
+    async fn consume(&self, entry: &LogEntry) -> Result<(), SinkError>;
+}
+
+
+/// Determines what a client does when one of its [LogSink]s returns an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SinkErrorPolicy {
+    /// Ignore the error and keep passing logs to all sinks. The poll
+    /// returns the logs as normal. This is the default policy.
+    #[default]
+    ContinueOthers,
+
+    /// Stop passing logs to the sinks and return the error from the poll.
+    /// The polled logs are returned inside the error, since the server has
+    /// already removed them.
+    StopPoll,
+}
+
+
+/// Container for the log sinks configured on a client
+#[derive(Clone, Default)]
+pub(crate) struct LogSinks {
+    sinks: Vec<Arc<dyn LogSink>>,
+    error_policy: SinkErrorPolicy,
+}
+
+impl LogSinks {
+    pub(crate) fn new(sinks: Vec<Arc<dyn LogSink>>, error_policy: SinkErrorPolicy) -> Self {
+        Self {
+            sinks,
+            error_policy,
+        }
+    }
+
+    /// Passes each of the provided logs to every configured sink in the
+    /// order the sinks were added, handling errors according to the
+    /// configured [SinkErrorPolicy]
+    pub(crate) async fn dispatch(&self, logs: &[LogEntry]) -> Result<(), SinkError> {
+        for log_entry in logs.iter() {
+            for sink in self.sinks.iter() {
+                let consume_result = sink.consume(log_entry).await;
+
+                if let Err(error) = consume_result {
+                    match self.error_policy {
+                        SinkErrorPolicy::ContinueOthers => continue,
+                        SinkErrorPolicy::StopPoll => return Err(error),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for LogSinks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogSinks")
+            .field("sink_count", &self.sinks.len())
+            .field("error_policy", &self.error_policy)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        received_logs: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl LogSink for RecordingSink {
+        async fn consume(&self, entry: &LogEntry) -> Result<(), SinkError> {
+            let log_string = match entry {
+                LogEntry::RawLog(raw_log) => raw_log.log_entry.clone(),
+                LogEntry::ParsedLog(parsed_log) => format!("{:?}", parsed_log),
+            };
+
+            self.received_logs.lock().unwrap().push(log_string);
+
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    #[async_trait::async_trait]
+    impl LogSink for FailingSink {
+        async fn consume(&self, _entry: &LogEntry) -> Result<(), SinkError> {
+            Err(SinkError::new("sink failure"))
+        }
+    }
+
+    fn get_test_logs() -> Vec<LogEntry> {
+        vec![
+            LogEntry::return_raw_log("first log"),
+            LogEntry::return_raw_log("second log"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn sink_receives_each_log() {
+        let recording_sink = Arc::new(RecordingSink::default());
+        let sinks: Vec<Arc<dyn LogSink>> = vec![recording_sink.clone()];
+        let log_sinks = LogSinks::new(sinks, SinkErrorPolicy::default());
+
+        log_sinks
+            .dispatch(&get_test_logs())
+            .await
+            .expect("Log sink dispatch failed");
+
+        let received_logs = recording_sink.received_logs.lock().unwrap();
+        assert_eq!(*received_logs, vec!["first log", "second log"]);
+    }
+
+    #[tokio::test]
+    async fn continue_others_policy_delivers_to_remaining_sinks() {
+        let recording_sink = Arc::new(RecordingSink::default());
+        let sinks: Vec<Arc<dyn LogSink>> = vec![Arc::new(FailingSink), recording_sink.clone()];
+        let log_sinks = LogSinks::new(sinks, SinkErrorPolicy::ContinueOthers);
+
+        log_sinks
+            .dispatch(&get_test_logs())
+            .await
+            .expect("Sink error was not ignored");
+
+        let received_logs = recording_sink.received_logs.lock().unwrap();
+        assert_eq!(received_logs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stop_poll_policy_returns_first_sink_error() {
+        let recording_sink = Arc::new(RecordingSink::default());
+        let sinks: Vec<Arc<dyn LogSink>> = vec![recording_sink.clone(), Arc::new(FailingSink)];
+        let log_sinks = LogSinks::new(sinks, SinkErrorPolicy::StopPoll);
+
+        log_sinks
+            .dispatch(&get_test_logs())
+            .await
+            .expect_err("Sink error was not returned");
+
+        let received_logs = recording_sink.received_logs.lock().unwrap();
+        assert_eq!(*received_logs, vec!["first log"]);
+    }
+}
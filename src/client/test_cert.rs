@@ -0,0 +1,33 @@
+//! A self-signed test certificate shared by the tests of the TLS options.
+
+use base64::engine::general_purpose;
+use base64::Engine as _;
+
+
+/// PEM encoding of a self-signed CA certificate
+pub(crate) const TEST_CERT_PEM: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBmDCCAT2gAwIBAgIUBE30DGxhWFb2HBFBhn3lUneBKJQwCgYIKoZIzj0EAwIw
+IDEeMBwGA1UEAwwVaW50ZXJhY3RzaC1ycyB0ZXN0IENBMCAXDTI2MTAxNjAzNDYy
+MloYDzIxMjYwOTIyMDM0NjIyWjAgMR4wHAYDVQQDDBVpbnRlcmFjdHNoLXJzIHRl
+c3QgQ0EwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAT+5qCttickjOarB25aCmus
+7ZH5NQfkWBtqLeOeVjjvMPOKAtxCcg9ftGorsgcwc190JeWHvpHfY4BttY4w9vhN
+o1MwUTAdBgNVHQ4EFgQUy56c6r4Q5fKbW2Jj5GxcbvAWlTkwHwYDVR0jBBgwFoAU
+y56c6r4Q5fKbW2Jj5GxcbvAWlTkwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQD
+AgNJADBGAiEA55jCyEZVNZZqx4ooJA4O89Y+tSv8RQZ02BMkIz5dnhgCIQCltaOW
+j6kmSeIvNTRf5/YfHc9je7u4QVnkvz/5acmosQ==
+-----END CERTIFICATE-----
+";
+
+/// Gets the DER encoding of the test certificate
+#[cfg_attr(not(feature = "cert-pinning"), allow(dead_code))]
+pub(crate) fn test_cert_der() -> Vec<u8> {
+    let cert_b64: String = TEST_CERT_PEM
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+
+    general_purpose::STANDARD
+        .decode(cert_b64)
+        .expect("Test certificate is not valid base64")
+}
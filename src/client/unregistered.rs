@@ -1,10 +1,85 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, HeaderValue};
 use secrecy::{ExposeSecret, Secret};
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
 
-use super::errors::{client_registration_error, ClientRegistrationError};
-use super::http_utils::{Client, RegisterData};
+use super::blocking_task;
+use super::builder::AuthToken;
+use super::correlation::{CorrelationConfig, CorrelationData, CorrelationGenerator};
+use super::errors::{
+    client_registration_error,
+    registration_error,
+    ClientRegistrationError,
+    RegistrationError,
+};
+use super::history::LogHistory;
+use super::http_utils::{
+    self,
+    Client,
+    DeregisterData,
+    EndpointPaths,
+    RegisterData,
+    RegisterResponse,
+    RegisterPayloadHook,
+    RequestCustomizer,
+    UserAgentRotation,
+};
 use super::registered::RegisteredClient;
-use crate::crypto::rsa::RSAPrivKey;
+use super::sink::LogSinks;
+use crate::crypto::errors::CryptoError;
+use crate::crypto::rsa::{OaepHash, RSAPrivKey, RsaPadding};
+
+/// Maximum number of times registration is retried with new correlation data
+/// when the server reports a correlation ID collision
+const MAX_COLLISION_RETRIES: usize = 3;
+
+/// The RSA key of an [UnregisteredClient], which may not be generated until
+/// the client registers
+#[derive(Debug, Clone)]
+pub(crate) enum RsaKeyState {
+    /// The key was generated or provided when the client was built
+    Ready {
+        rsa_key: Box<RSAPrivKey>,
+        encoded_pub_key: String,
+    },
+
+    /// The key is generated when the client registers
+    Deferred { key_size: usize },
+}
+
+impl RsaKeyState {
+    /// Generates the key if it was deferred, on a separate thread so that
+    /// the async executor isn't blocked
+    async fn generate_deferred(&mut self) -> Result<(), CryptoError> {
+        if let RsaKeyState::Deferred { key_size } = *self {
+            *self = blocking_task::run_blocking(move || Self::generate(key_size)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Generates a new key of the provided size and encodes its public key
+    fn generate(key_size: usize) -> Result<Self, CryptoError> {
+        let rsa_key = RSAPrivKey::generate(key_size)?;
+        let encoded_pub_key = rsa_key.get_pub_key()?.b64_encode()?;
+
+        Ok(RsaKeyState::Ready {
+            rsa_key: Box::new(rsa_key),
+            encoded_pub_key,
+        })
+    }
+
+    /// Gets the encoded public key, or None if the key is still deferred
+    pub(crate) fn encoded_pub_key(&self) -> Option<&str> {
+        match self {
+            RsaKeyState::Ready {
+                encoded_pub_key, ..
+            } => Some(encoded_pub_key),
+            RsaKeyState::Deferred { .. } => None,
+        }
+    }
+}
 
 /// The client type returned by the [ClientBuilder](crate::client::ClientBuilder)
 /// build function.
@@ -14,15 +89,34 @@ use crate::crypto::rsa::RSAPrivKey;
 /// be used to poll an Interactsh server.
 #[derive(Debug, Clone)]
 pub struct UnregisteredClient {
-    pub(crate) rsa_key: RSAPrivKey,
+    pub(crate) rsa_key: RsaKeyState,
     pub(crate) server: String,
+    pub(crate) server_url: String,
+    pub(crate) trailing_slash: bool,
+    pub(crate) endpoint_paths: EndpointPaths,
     pub(crate) sub_domain: String,
     pub(crate) correlation_id: String,
-    pub(crate) auth_token: Option<Secret<String>>,
+    pub(crate) auth_token: Option<AuthToken>,
     pub(crate) secret_key: Secret<String>,
-    pub(crate) encoded_pub_key: String,
     pub(crate) reqwest_client: reqwest::Client,
     pub(crate) parse_logs: bool,
+    pub(crate) retry_on_collision: bool,
+    pub(crate) auto_correlation: bool,
+    pub(crate) correlation_config: CorrelationConfig,
+    pub(crate) retry_on_parse_failure: bool,
+    pub(crate) log_sinks: LogSinks,
+    pub(crate) keepalive_interval: Option<Duration>,
+    pub(crate) poll_timeout: Option<Duration>,
+    pub(crate) user_agents: UserAgentRotation,
+    pub(crate) custom_headers: Vec<(HeaderName, HeaderValue)>,
+    pub(crate) request_customizer: Option<RequestCustomizer>,
+    pub(crate) correlation_generator: Option<CorrelationGenerator>,
+    pub(crate) register_payload_hook: Option<RegisterPayloadHook>,
+    pub(crate) rsa_padding: RsaPadding,
+    pub(crate) oaep_hash: OaepHash,
+    pub(crate) max_history: usize,
+    #[cfg(feature = "async-compat")]
+    pub(crate) deregister_on_drop: bool,
 }
 
 impl UnregisteredClient {
@@ -32,46 +126,587 @@ impl UnregisteredClient {
     /// that can be used to poll the server. If the registration fails, this returns
     /// a [ClientRegistrationError](super::errors::ClientRegistrationError), which
     /// contains a clone of this client if another try is needed.
+    ///
+    /// If the client was built with
+    /// [retry_on_collision](crate::client::ClientBuilder::retry_on_collision()) set,
+    /// a correlation ID collision reported by the server will cause the client to
+    /// generate a new subdomain and correlation ID and try again.
+    ///
+    /// If the server assigns its own subdomain and returns it in the registration
+    /// response, the client uses the server's subdomain for the interaction FQDN.
+    /// The rest of the response is available from
+    /// [get_register_response](crate::client::RegisteredClient::get_register_response()).
+    ///
+    /// If the client was built with
+    /// [auto_correlation](crate::client::ClientBuilder::auto_correlation()) set,
+    /// the client registers again if the server reports different correlation
+    /// lengths than the ones the client used. Registrations that are discarded
+    /// this way, or because the server doesn't report its correlation lengths,
+    /// are deregistered with the server.
+    ///
+    /// If the client was built with [lazy_key](crate::client::ClientBuilder::lazy_key())
+    /// set, the RSA key is generated on a separate thread before registering.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                otel.kind = "client",
+                peer.service = %self.server,
+                correlation_id = %self.correlation_id,
+            )
+        )
+    )]
     pub async fn register(
-        self,
+        mut self,
     ) -> Result<RegisteredClient, ClientRegistrationError<UnregisteredClient>> {
+        if let Err(error) = self.rsa_key.generate_deferred().await {
+            return Err(error)
+                .context(registration_error::RsaGen)
+                .context(client_registration_error::ClientRegistration { client: self });
+        }
+
+        let mut collision_retries = 0;
+        let mut reregistered_for_correlation = false;
+
+        let register_response = loop {
+            let registration_result = match self.register_payload() {
+                Ok(post_data) => self.do_registration_request(post_data).await,
+                Err(error) => Err(error),
+            };
+
+            match registration_result {
+                Ok(register_response) => {
+                    if self.auto_correlation {
+                        let server_config = match register_response.correlation_config() {
+                            Some(server_config) => server_config,
+                            None => {
+                                self.deregister_discarded().await;
+                                let error =
+                                    registration_error::CorrelationDiscoveryUnsupported.build();
+
+                                return Err(error).context(
+                                    client_registration_error::ClientRegistration { client: self },
+                                );
+                            }
+                        };
+
+                        // Register again with correlation data the server can correlate
+                        if server_config != self.correlation_config && !reregistered_for_correlation
+                        {
+                            reregistered_for_correlation = true;
+                            self.correlation_config = server_config;
+                            self.deregister_discarded().await;
+
+                            if let Err(error) = self.regenerate_correlation_data() {
+                                return Err(error).context(
+                                    client_registration_error::ClientRegistration { client: self },
+                                );
+                            }
+
+                            continue;
+                        }
+                    }
+
+                    // Use the subdomain assigned by the server if there is one
+                    if let Some(sub_domain) = register_response.assigned_sub_domain(&self.server) {
+                        self.sub_domain = sub_domain;
+                    }
+
+                    break register_response;
+                }
+                Err(RegistrationError::CorrelationIdExists { .. })
+                    if self.retry_on_collision && collision_retries < MAX_COLLISION_RETRIES =>
+                {
+                    collision_retries += 1;
+
+                    if let Err(error) = self.regenerate_correlation_data() {
+                        return Err(error).context(client_registration_error::ClientRegistration {
+                            client: self,
+                        });
+                    }
+                }
+                Err(error) => {
+                    return Err(error)
+                        .context(client_registration_error::ClientRegistration { client: self });
+                }
+            }
+        };
+
+        let mut registered_client = self.into_registered()?;
+        registered_client.register_response = register_response;
+
+        Ok(registered_client)
+    }
+
+    /// Gets the interaction FQDN that the client is expected to have once
+    /// registered, so that payloads can be prepared before registering.
+    ///
+    /// This returns None if the client was built with
+    /// [retry_on_collision](crate::client::ClientBuilder::retry_on_collision())
+    /// or [auto_correlation](crate::client::ClientBuilder::auto_correlation())
+    /// set, since the subdomain may be regenerated during registration. The
+    /// prediction also won't match if the server assigns its own subdomain.
+    pub fn predicted_fqdn(&self) -> Option<String> {
+        if self.retry_on_collision || self.auto_correlation {
+            return None;
+        }
+
+        Some(format!("{}.{}", self.sub_domain, self.server))
+    }
+
+    /// Replaces the subdomain and correlation ID with newly generated ones
+    /// that have the lengths of the client's correlation config
+    fn regenerate_correlation_data(&mut self) -> Result<(), RegistrationError> {
+        let correlation_data = CorrelationData::generate_with(
+            self.correlation_generator.as_ref(),
+            &self.correlation_config,
+        );
+        ensure!(
+            correlation_data.has_valid_lengths(&self.correlation_config),
+            registration_error::InvalidCorrelationData {
+                subdomain_len: correlation_data.sub_domain.len(),
+                correlation_id_len: correlation_data.correlation_id.len(),
+            }
+        );
+
+        self.sub_domain = correlation_data.sub_domain;
+        self.correlation_id = correlation_data.correlation_id;
+
+        Ok(())
+    }
+
+    /// Deregisters the session that the server accepted for the current
+    /// correlation ID before the client discards it
+    ///
+    /// This is best effort, the same as deregistering on drop, so a failure
+    /// doesn't affect the result of the registration.
+    async fn deregister_discarded(&self) {
+        let deregister_url = http_utils::endpoint_url(
+            &self.server_url,
+            &self.endpoint_paths.deregister,
+            self.trailing_slash,
+        );
+        let post_data = DeregisterData {
+            correlation_id: self.correlation_id.clone(),
+            secret_key: self.secret_key.expose_secret().clone(),
+        };
+
+        let _deregister_result = self
+            .do_registration_request_to(deregister_url, post_data)
+            .await;
+    }
+
+    /// Serializes the register request body, running the register payload
+    /// hook on it if one was set
+    fn register_payload(&self) -> Result<serde_json::Value, RegistrationError> {
         let post_data = RegisterData {
-            public_key: self.encoded_pub_key.clone(),
+            public_key: self
+                .rsa_key
+                .encoded_pub_key()
+                .context(registration_error::RsaKeyNotGenerated)?
+                .to_string(),
             secret_key: self.secret_key.expose_secret().clone(),
             correlation_id: self.correlation_id.clone(),
         };
 
-        self.do_registration_request(post_data).await.context(
-            client_registration_error::ClientRegistration {
-                client: self.clone(),
-            },
-        )?;
+        let mut payload =
+            serde_json::to_value(post_data).context(registration_error::PayloadSerializeFailed)?;
+
+        if let Some(hook) = &self.register_payload_hook {
+            hook.apply(&mut payload);
+        }
+
+        Ok(payload)
+    }
+
+    /// Converts this client into a [RegisteredClient] without contacting the
+    /// server. The RSA key must already be generated, since the server only
+    /// knows the public key that was sent when registering.
+    // The error hands back the client, the same as the error of register, so
+    // it is not boxed here either
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn into_registered(
+        self,
+    ) -> Result<RegisteredClient, ClientRegistrationError<UnregisteredClient>> {
+        let rsa_key = match &self.rsa_key {
+            RsaKeyState::Ready { rsa_key, .. } => RSAPrivKey::clone(rsa_key),
+            RsaKeyState::Deferred { .. } => {
+                return registration_error::RsaKeyNotGenerated
+                    .fail()
+                    .context(client_registration_error::ClientRegistration { client: self });
+            }
+        };
+
+        Ok(self.into_registered_with_key(rsa_key))
+    }
+
+    /// Converts this client into a [RegisteredClient] using the provided RSA
+    /// key in place of the client's own key
+    pub(crate) fn into_registered_with_key(self, rsa_key: RSAPrivKey) -> RegisteredClient {
+        #[cfg(feature = "async-compat")]
+        let deregister_on_drop = self.deregister_on_drop;
 
-        let new_reg_client = RegisteredClient {
-            rsa_key: self.rsa_key,
+        let registered_client = RegisteredClient {
+            rsa_key,
             server: self.server,
+            server_url: self.server_url,
+            trailing_slash: self.trailing_slash,
+            endpoint_paths: self.endpoint_paths,
             sub_domain: self.sub_domain,
             correlation_id: self.correlation_id,
             auth_token: self.auth_token,
             secret_key: self.secret_key,
             reqwest_client: self.reqwest_client,
             parse_logs: self.parse_logs,
+            retry_on_parse_failure: self.retry_on_parse_failure,
+            log_sinks: self.log_sinks,
+            keepalive_interval: self.keepalive_interval,
+            poll_timeout: self.poll_timeout,
+            user_agents: self.user_agents,
+            custom_headers: self.custom_headers,
+            request_customizer: self.request_customizer,
+            rsa_padding: self.rsa_padding,
+            oaep_hash: self.oaep_hash,
+            log_history: LogHistory::new(self.max_history),
+            register_response: RegisterResponse::default(),
+            registered: true,
+            last_decrypt_duration: Default::default(),
+            #[cfg(feature = "async-compat")]
+            deregister_guard: None,
+            #[cfg(all(feature = "test-utils", debug_assertions))]
+            last_aes_key: Default::default(),
         };
 
-        Ok(new_reg_client)
+        #[cfg(feature = "async-compat")]
+        if deregister_on_drop {
+            return registered_client.with_deregister_guard();
+        }
+
+        registered_client
     }
 }
 
 impl Client for UnregisteredClient {
     fn get_registration_url(&self) -> String {
-        format!("https://{}/register", &self.server)
+        http_utils::endpoint_url(
+            &self.server_url,
+            &self.endpoint_paths.register,
+            self.trailing_slash,
+        )
     }
 
     fn get_reqwest_client(&self) -> &reqwest::Client {
         &self.reqwest_client
     }
 
-    fn get_auth_token(&self) -> Option<&Secret<String>> {
+    fn get_auth_token(&self) -> Option<&AuthToken> {
         self.auth_token.as_ref()
     }
+
+    fn get_user_agents(&self) -> &UserAgentRotation {
+        &self.user_agents
+    }
+
+    fn get_custom_headers(&self) -> &[(HeaderName, HeaderValue)] {
+        &self.custom_headers
+    }
+
+    fn get_request_customizer(&self) -> Option<&RequestCustomizer> {
+        self.request_customizer.as_ref()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::client::mock_server::{MockResponse, MockServer};
+    use crate::client::ClientBuilder;
+
+    #[test]
+    fn register_payload_contains_register_data() {
+        let client = ClientBuilder::default()
+            .build()
+            .expect("Default build failed");
+
+        let payload = client
+            .register_payload()
+            .expect("Failed to serialize the register payload");
+
+        assert_eq!(payload["correlation-id"], json!(client.correlation_id));
+        let encoded_pub_key = client
+            .rsa_key
+            .encoded_pub_key()
+            .expect("Failed to encode the public key");
+        assert_eq!(payload["public-key"], json!(encoded_pub_key));
+    }
+
+    #[test]
+    fn register_payload_hook_modifies_payload() {
+        let client = ClientBuilder::default()
+            .with_register_payload_hook(|payload| {
+                payload["extra-field"] = json!("extra value");
+            })
+            .build()
+            .expect("Build with a register payload hook failed");
+
+        let payload = client
+            .register_payload()
+            .expect("Failed to serialize the register payload");
+
+        assert_eq!(payload["extra-field"], json!("extra value"));
+        assert_eq!(payload["correlation-id"], json!(client.correlation_id));
+    }
+
+    #[test]
+    fn regenerated_correlation_data_uses_client_config() {
+        let mut client = ClientBuilder::default()
+            .auto_correlation(true)
+            .build()
+            .expect("Build with auto_correlation failed");
+        client.correlation_config = CorrelationConfig::new(16, 8);
+
+        client
+            .regenerate_correlation_data()
+            .expect("Failed to regenerate the correlation data");
+
+        assert_eq!(client.sub_domain.len(), 16);
+        assert_eq!(client.correlation_id.len(), 8);
+        assert!(client.predicted_fqdn().is_none());
+    }
+
+    #[test]
+    fn predicted_fqdn_matches_registered_fqdn() {
+        let client = ClientBuilder::default()
+            .build()
+            .expect("Default build failed");
+
+        let predicted_fqdn = client
+            .predicted_fqdn()
+            .expect("No FQDN predicted without retry_on_collision");
+        let registered_client = client
+            .into_registered()
+            .expect("Failed to convert the client");
+
+        assert_eq!(predicted_fqdn, registered_client.get_interaction_fqdn());
+    }
+
+    #[test]
+    fn predicted_fqdn_is_none_with_retry_on_collision() {
+        let client = ClientBuilder::default()
+            .retry_on_collision(true)
+            .build()
+            .expect("Build with retry_on_collision failed");
+
+        assert!(client.predicted_fqdn().is_none());
+    }
+
+    #[tokio::test]
+    async fn registration_is_retried_with_new_correlation_id_on_collision() {
+        let server = MockServer::start();
+        server.queue_response(
+            "/register",
+            MockResponse::status(400, "correlation-id provided already exists"),
+        );
+        server.queue_response("/register", MockResponse::ok("registration successful"));
+        let client = server
+            .client_builder()
+            .retry_on_collision(true)
+            .build()
+            .expect("Build with retry_on_collision failed");
+
+        let registered_client = client
+            .register()
+            .await
+            .expect("Registration was not retried after the collision");
+
+        let register_requests = server.requests_to("/register");
+        assert_eq!(register_requests.len(), 2);
+        let first_correlation_id = &register_requests[0].json_body()["correlation-id"];
+        let second_correlation_id = &register_requests[1].json_body()["correlation-id"];
+        assert_ne!(first_correlation_id, second_correlation_id);
+        assert_eq!(
+            second_correlation_id,
+            &json!(registered_client.get_correlation_id())
+        );
+    }
+
+    #[tokio::test]
+    async fn registration_collision_fails_without_retry_on_collision() {
+        let server = MockServer::start();
+        server.queue_response(
+            "/register",
+            MockResponse::status(400, "correlation-id provided already exists"),
+        );
+        let client = server.client_builder().build().expect("Build failed");
+
+        let registration_error = match client.register().await {
+            Ok(_) => panic!("Registration succeeded after a collision"),
+            Err(registration_error) => registration_error,
+        };
+
+        assert!(matches!(
+            registration_error.error,
+            RegistrationError::CorrelationIdExists { .. }
+        ));
+        assert_eq!(server.requests_to("/register").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn server_assigned_subdomain_is_adopted() {
+        let server = MockServer::start();
+        server.queue_response(
+            "/register",
+            MockResponse::ok(r#"{"subdomain": "serverassignedsubdomain"}"#),
+        );
+        let client = server.client_builder().build().expect("Build failed");
+
+        let registered_client = client
+            .register()
+            .await
+            .expect("Registration with the mock server failed");
+
+        assert_eq!(
+            registered_client.get_interaction_fqdn(),
+            "serverassignedsubdomain.127.0.0.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn plain_text_register_response_keeps_generated_subdomain() {
+        let server = MockServer::start();
+        server.queue_response("/register", MockResponse::ok("registration successful"));
+        let client = server.client_builder().build().expect("Build failed");
+        let predicted_fqdn = client.predicted_fqdn();
+
+        let registered_client = client
+            .register()
+            .await
+            .expect("Registration with the mock server failed");
+
+        assert_eq!(
+            Some(registered_client.get_interaction_fqdn()),
+            predicted_fqdn
+        );
+    }
+
+    #[tokio::test]
+    async fn register_payload_hook_field_is_sent_to_the_server() {
+        let server = MockServer::start();
+        server.queue_response("/register", MockResponse::ok("registration successful"));
+        let client = server
+            .client_builder()
+            .with_register_payload_hook(|payload| {
+                payload["extra-field"] = json!("extra value");
+            })
+            .build()
+            .expect("Build with a register payload hook failed");
+        let correlation_id = client.correlation_id.clone();
+
+        client
+            .register()
+            .await
+            .expect("Registration with the mock server failed");
+
+        let register_requests = server.requests_to("/register");
+        assert_eq!(register_requests.len(), 1);
+        let register_body = register_requests[0].json_body();
+        assert_eq!(register_body["extra-field"], json!("extra value"));
+        assert_eq!(register_body["correlation-id"], json!(correlation_id));
+    }
+
+    #[tokio::test]
+    async fn registration_discarded_for_correlation_lengths_is_deregistered() {
+        let server = MockServer::start();
+        server.queue_response(
+            "/register",
+            MockResponse::ok(r#"{"correlation-id-length":16,"correlation-id-nonce-length":8}"#),
+        );
+        server.queue_response("/deregister", MockResponse::ok("deregistration successful"));
+        let client = server
+            .client_builder()
+            .auto_correlation(true)
+            .build()
+            .expect("Build with auto_correlation failed");
+
+        let registered_client = client
+            .register()
+            .await
+            .expect("Registration with the mock server failed");
+
+        let register_requests = server.requests_to("/register");
+        let deregister_requests = server.requests_to("/deregister");
+        assert_eq!(register_requests.len(), 2);
+        assert_eq!(deregister_requests.len(), 1);
+        assert_eq!(
+            deregister_requests[0].json_body()["correlation-id"],
+            register_requests[0].json_body()["correlation-id"]
+        );
+        assert_eq!(registered_client.get_correlation_id().len(), 16);
+    }
+
+    #[tokio::test]
+    async fn registration_without_correlation_lengths_is_deregistered() {
+        let server = MockServer::start();
+        server.queue_response("/register", MockResponse::ok("registration successful"));
+        server.queue_response("/deregister", MockResponse::ok("deregistration successful"));
+        let client = server
+            .client_builder()
+            .auto_correlation(true)
+            .build()
+            .expect("Build with auto_correlation failed");
+
+        let registration_error = match client.register().await {
+            Ok(_) => panic!("Registration succeeded without correlation discovery"),
+            Err(registration_error) => registration_error,
+        };
+
+        assert!(matches!(
+            registration_error.error,
+            RegistrationError::CorrelationDiscoveryUnsupported { .. }
+        ));
+        let register_requests = server.requests_to("/register");
+        let deregister_requests = server.requests_to("/deregister");
+        assert_eq!(register_requests.len(), 1);
+        assert_eq!(deregister_requests.len(), 1);
+        assert_eq!(
+            deregister_requests[0].json_body()["correlation-id"],
+            register_requests[0].json_body()["correlation-id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn deferred_rsa_key_is_generated() {
+        let mut rsa_key = RsaKeyState::Deferred { key_size: 2048 };
+
+        rsa_key
+            .generate_deferred()
+            .await
+            .expect("Deferred RSA key generation failed");
+
+        let encoded_pub_key = rsa_key
+            .encoded_pub_key()
+            .expect("Failed to encode the public key");
+        assert!(!encoded_pub_key.is_empty());
+    }
+
+    #[test]
+    fn deferred_rsa_key_is_not_converted_into_registered() {
+        let client = ClientBuilder::default()
+            .lazy_key(true)
+            .build()
+            .expect("Build with a lazy key failed");
+
+        let conversion_error = client
+            .into_registered()
+            .expect_err("Client with a deferred key was converted");
+
+        assert!(matches!(
+            conversion_error.error,
+            RegistrationError::RsaKeyNotGenerated { .. }
+        ));
+    }
 }
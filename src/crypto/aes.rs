@@ -1,9 +1,33 @@
 //! Defines the functions necessary for decrypting AES-encrypted data returned by the Interactsh servers.
 
+use zeroize::Zeroizing;
+
 use super::errors::CryptoError;
 
+/// Length of the IV that prefixes the AES encrypted data
+const AES_IV_LEN: usize = 16;
+
 /// Decrypt the provided data using the provided plain-text AES key
-pub(crate) fn decrypt_data(aes_key: &[u8], encrypted_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+///
+/// The AES variant is picked based on the key length: 16, 24, and 32 byte keys
+/// use AES-128, AES-192, and AES-256 respectively. The decrypted data is
+/// zeroized when dropped.
+///
+/// The encrypted data must start with the 16 byte IV; shorter data returns an
+/// error instead of being decrypted.
+pub(crate) fn decrypt_data(
+    aes_key: &[u8],
+    encrypted_data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    use super::errors::crypto_error;
+
+    if encrypted_data.len() < AES_IV_LEN {
+        return crypto_error::AesDataLength {
+            data_len: encrypted_data.len(),
+        }
+        .fail();
+    }
+
     cfg_if::cfg_if! {
         if #[cfg(feature = "rustcrypto")] {
             rustcrypto_decrypt(aes_key, encrypted_data)
@@ -15,34 +39,150 @@ pub(crate) fn decrypt_data(aes_key: &[u8], encrypted_data: &[u8]) -> Result<Vec<
 
 /// Decrypt the provided data using the provided plain-text AES key (using RustCrypto libraries)
 #[cfg(feature = "rustcrypto")]
-fn rustcrypto_decrypt(aes_key: &[u8], encrypted_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
-    use aes::cipher::{AsyncStreamCipher, KeyIvInit};
-    type Aes256CfbDec = cfb_mode::Decryptor<aes::Aes256>;
-
-    let iv = &encrypted_data[0..16];
+fn rustcrypto_decrypt(
+    aes_key: &[u8],
+    encrypted_data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    use super::errors::crypto_error;
 
-    let decryptor = Aes256CfbDec::new(aes_key.into(), iv.into());
+    let iv = &encrypted_data[0..AES_IV_LEN];
 
-    let mut decrypted_data = encrypted_data[16..].to_vec();
-    decryptor.decrypt(&mut decrypted_data);
+    let mut decrypted_data = Zeroizing::new(encrypted_data[AES_IV_LEN..].to_vec());
+    match aes_key.len() {
+        16 => rustcrypto_cfb_decrypt::<aes::Aes128>(aes_key, iv, &mut decrypted_data),
+        24 => rustcrypto_cfb_decrypt::<aes::Aes192>(aes_key, iv, &mut decrypted_data),
+        32 => rustcrypto_cfb_decrypt::<aes::Aes256>(aes_key, iv, &mut decrypted_data),
+        key_len => return crypto_error::AesKeySize { key_len }.fail(),
+    }
 
     Ok(decrypted_data)
 }
 
+/// Decrypt the data in place with the AES variant matching the key size (using RustCrypto libraries)
+#[cfg(feature = "rustcrypto")]
+fn rustcrypto_cfb_decrypt<C>(aes_key: &[u8], iv: &[u8], data: &mut [u8])
+where
+    C: aes::cipher::BlockCipher + aes::cipher::BlockEncryptMut + aes::cipher::KeyInit,
+{
+    use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+
+    let decryptor = cfb_mode::Decryptor::<C>::new(aes_key.into(), iv.into());
+    decryptor.decrypt(data);
+}
+
 
 /// Decrypt the provided data using the provided plain-text AES key (using the OpenSSL library)
 #[cfg(all(feature = "openssl", not(feature = "rustcrypto")))]
-fn openssl_decrypt(aes_key: &[u8], encrypted_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+fn openssl_decrypt(
+    aes_key: &[u8],
+    encrypted_data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
     use snafu::ResultExt;
 
     use super::errors::crypto_error;
 
-    let iv = &encrypted_data[0..16];
-    let cipher = openssl::symm::Cipher::aes_256_cfb128();
-    let sliced_encrypted_data = &encrypted_data[16..];
+    let iv = &encrypted_data[0..AES_IV_LEN];
+    let cipher = match aes_key.len() {
+        16 => openssl::symm::Cipher::aes_128_cfb128(),
+        24 => openssl::symm::Cipher::aes_192_cfb128(),
+        32 => openssl::symm::Cipher::aes_256_cfb128(),
+        key_len => return crypto_error::AesKeySize { key_len }.fail(),
+    };
+    let sliced_encrypted_data = &encrypted_data[AES_IV_LEN..];
 
     let decrypted_data = openssl::symm::decrypt(cipher, aes_key, Some(iv), sliced_encrypted_data)
         .context(crypto_error::AesDecrypt)?;
 
-    Ok(decrypted_data)
+    Ok(Zeroizing::new(decrypted_data))
+}
+
+/// Encrypt the provided data using the provided plain-text AES key and IV the same
+/// way an Interactsh server does, returning the IV followed by the encrypted data
+///
+/// Only used for testing.
+#[cfg(test)]
+pub(crate) fn encrypt_data(aes_key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "rustcrypto")] {
+            use aes::cipher::{AsyncStreamCipher, KeyIvInit};
+
+            let mut encrypted_data = data.to_vec();
+            match aes_key.len() {
+                16 => cfb_mode::Encryptor::<aes::Aes128>::new(aes_key.into(), iv.into())
+                    .encrypt(&mut encrypted_data),
+                24 => cfb_mode::Encryptor::<aes::Aes192>::new(aes_key.into(), iv.into())
+                    .encrypt(&mut encrypted_data),
+                _ => cfb_mode::Encryptor::<aes::Aes256>::new(aes_key.into(), iv.into())
+                    .encrypt(&mut encrypted_data),
+            }
+        } else if #[cfg(feature = "openssl")] {
+            let cipher = match aes_key.len() {
+                16 => openssl::symm::Cipher::aes_128_cfb128(),
+                24 => openssl::symm::Cipher::aes_192_cfb128(),
+                _ => openssl::symm::Cipher::aes_256_cfb128(),
+            };
+            let encrypted_data = openssl::symm::encrypt(cipher, aes_key, Some(iv), data)
+                .expect("AES encryption failed");
+        }
+    }
+
+    let mut output = iv.to_vec();
+    output.extend(encrypted_data);
+
+    output
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(key_len: usize) {
+        let aes_key = vec![7u8; key_len];
+        let iv = [3u8; 16];
+        let data = b"interaction log data";
+
+        let encrypted_data = encrypt_data(&aes_key, &iv, data);
+        let decrypted_data =
+            decrypt_data(&aes_key, &encrypted_data).expect("Failed to decrypt the data");
+
+        assert_eq!(decrypted_data.as_slice(), data);
+    }
+
+    #[test]
+    fn aes_128_key_decrypts_successfully() {
+        round_trip(16);
+    }
+
+    #[test]
+    fn aes_192_key_decrypts_successfully() {
+        round_trip(24);
+    }
+
+    #[test]
+    fn aes_256_key_decrypts_successfully() {
+        round_trip(32);
+    }
+
+    #[test]
+    fn unexpected_key_length_fails() {
+        let encrypted_data = [0u8; 32];
+
+        let decrypt_result = decrypt_data(&[0u8; 20], &encrypted_data);
+
+        assert!(matches!(
+            decrypt_result,
+            Err(CryptoError::AesKeySize { key_len: 20, .. })
+        ));
+    }
+
+    #[test]
+    fn data_shorter_than_iv_fails() {
+        let decrypt_result = decrypt_data(&[0u8; 32], &[1u8, 2, 3]);
+
+        assert!(matches!(
+            decrypt_result,
+            Err(CryptoError::AesDataLength { data_len: 3, .. })
+        ));
+    }
 }
@@ -29,7 +29,16 @@ mod errors_to_reexport {
     #[cfg(feature = "rustcrypto")]
     #[derive(Debug, Snafu)]
     #[snafu(module(rustcrypto_error), context(suffix(false)), visibility(pub))]
+    #[non_exhaustive]
     pub enum RustCryptoError {
+        #[snafu(display(
+            "AES encrypted data is shorter than the 16 byte IV (length: {data_len} bytes)"
+        ))]
+        AesDataLength { data_len: usize },
+
+        #[snafu(display("Unsupported AES key length (length: {key_len} bytes)"))]
+        AesKeySize { key_len: usize },
+
         #[snafu(display("Failed to decode the data using base 64 encoding"))]
         Base64DecodeAes { source: base64::DecodeError },
 
@@ -41,6 +50,12 @@ mod errors_to_reexport {
 
         #[snafu(display("Failed to decrypt the data with the provided RSA private key"))]
         RsaDecrypt { source: rsa::errors::Error },
+
+        #[snafu(display("Failed to encode the RSA private key as PEM"))]
+        RsaPemEncode { source: rsa::pkcs8::Error },
+
+        #[snafu(display("Failed to decode the RSA private key from PEM"))]
+        RsaPemDecode { source: rsa::pkcs8::Error },
     }
 
 
@@ -48,10 +63,19 @@ mod errors_to_reexport {
     #[cfg(feature = "openssl")]
     #[derive(Debug, Snafu)]
     #[snafu(module, context(suffix(false)), visibility(pub))]
+    #[non_exhaustive]
     pub enum OpensslError {
         #[snafu(display("Unable to decrypt data with provided AES key"))]
         AesDecrypt { source: openssl::error::ErrorStack },
 
+        #[snafu(display(
+            "AES encrypted data is shorter than the 16 byte IV (length: {data_len} bytes)"
+        ))]
+        AesDataLength { data_len: usize },
+
+        #[snafu(display("Unsupported AES key length (length: {key_len} bytes)"))]
+        AesKeySize { key_len: usize },
+
         #[snafu(display("Failed to decode the data using base 64 encoding"))]
         Base64DecodeAes { source: base64::DecodeError },
 
@@ -72,5 +96,14 @@ mod errors_to_reexport {
 
         #[snafu(display("Failed to extract the RSA public key from the RSA private key"))]
         RsaGetPubKey { source: openssl::error::ErrorStack },
+
+        #[snafu(display("Failed to encode the RSA private key as PEM"))]
+        RsaPemEncode { source: openssl::error::ErrorStack },
+
+        #[snafu(display("Failed to decode the RSA private key from PEM"))]
+        RsaPemDecode { source: openssl::error::ErrorStack },
+
+        #[snafu(display("Failed to read the modulus and exponent of the RSA public key"))]
+        RsaPubKeyComponents { source: openssl::error::ErrorStack },
     }
 }
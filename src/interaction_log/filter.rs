@@ -0,0 +1,163 @@
+//! Iterator adapters for picking out the parsed logs of a protocol from a
+//! batch of logs.
+
+use super::{LogEntry, LogProtocol, ParsedLogEntry};
+
+
+/// Filters logs down to their parsed logs, optionally of a single protocol
+///
+/// Implemented for anything that iterates over `&LogEntry`, such as
+/// `&[LogEntry]`, `&Vec<LogEntry>`, or `logs.iter()`.
+pub trait LogEntryFilterExt<'a>: Sized {
+    /// The iterator over the logs being filtered
+    type Logs: Iterator<Item = &'a LogEntry>;
+
+    /// Gets an iterator over the parsed logs, skipping the raw logs
+    fn parsed_only(self) -> FilteredLogs<Self::Logs>;
+
+    /// Gets an iterator over the parsed logs of the provided protocol
+    fn protocol_only(self, protocol: LogProtocol) -> FilteredLogs<Self::Logs>;
+
+    /// Gets an iterator over the parsed DNS logs
+    fn dns_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Dns)
+    }
+
+    /// Gets an iterator over the parsed HTTP logs
+    fn http_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Http)
+    }
+
+    /// Gets an iterator over the parsed FTP logs
+    fn ftp_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Ftp)
+    }
+
+    /// Gets an iterator over the parsed SMTP logs
+    fn smtp_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Smtp)
+    }
+
+    /// Gets an iterator over the parsed LDAP logs
+    fn ldap_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Ldap)
+    }
+
+    /// Gets an iterator over the parsed SMB logs
+    fn smb_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Smb)
+    }
+
+    /// Gets an iterator over the parsed Responder logs
+    fn responder_only(self) -> FilteredLogs<Self::Logs> {
+        self.protocol_only(LogProtocol::Responder)
+    }
+}
+
+impl<'a, T> LogEntryFilterExt<'a> for T
+where
+    T: IntoIterator<Item = &'a LogEntry>,
+{
+    type Logs = T::IntoIter;
+
+    fn parsed_only(self) -> FilteredLogs<Self::Logs> {
+        FilteredLogs {
+            logs: self.into_iter(),
+            protocol: None,
+        }
+    }
+
+    fn protocol_only(self, protocol: LogProtocol) -> FilteredLogs<Self::Logs> {
+        FilteredLogs {
+            logs: self.into_iter(),
+            protocol: Some(protocol),
+        }
+    }
+}
+
+
+/// Iterator over the parsed logs that pass a [LogEntryFilterExt] filter
+#[derive(Debug, Clone)]
+pub struct FilteredLogs<I> {
+    logs: I,
+    protocol: Option<LogProtocol>,
+}
+
+impl<'a, I> Iterator for FilteredLogs<I>
+where
+    I: Iterator<Item = &'a LogEntry>,
+{
+    type Item = &'a ParsedLogEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for log_entry in self.logs.by_ref() {
+            let parsed_log = match log_entry {
+                LogEntry::ParsedLog(parsed_log) => parsed_log,
+                LogEntry::RawLog(_) => continue,
+            };
+
+            match &self.protocol {
+                Some(protocol) if parsed_log.log_protocol() != *protocol => continue,
+                _ => return Some(parsed_log),
+            }
+        }
+
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn smb_log_entry() -> LogEntry {
+        LogEntry::ParsedLog(ParsedLogEntry::Smb {
+            raw_request: "smb request".into(),
+            timestamp: OffsetDateTime::now_utc(),
+        })
+    }
+
+    fn http_log_entry() -> LogEntry {
+        LogEntry::ParsedLog(ParsedLogEntry::Http {
+            unique_id: "cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb".into(),
+            full_id: "cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb".into(),
+            raw_request: "GET / HTTP/1.1".into(),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        })
+    }
+
+    #[test]
+    fn logs_are_filtered_by_protocol() {
+        let logs = vec![
+            http_log_entry(),
+            smb_log_entry(),
+            LogEntry::return_raw_log("raw log"),
+            http_log_entry(),
+        ];
+
+        assert_eq!(logs.http_only().count(), 2);
+        assert_eq!(logs.iter().smb_only().count(), 1);
+        assert_eq!(logs[..].dns_only().count(), 0);
+        assert!(logs
+            .http_only()
+            .all(|parsed_log| parsed_log.log_protocol() == LogProtocol::Http));
+    }
+
+    #[test]
+    fn raw_logs_are_skipped_by_parsed_only() {
+        let logs = vec![
+            LogEntry::return_raw_log("raw log"),
+            smb_log_entry(),
+            http_log_entry(),
+        ];
+
+        let protocols: Vec<&str> = logs.parsed_only().map(ParsedLogEntry::protocol).collect();
+
+        assert_eq!(protocols, vec!["smb", "http"]);
+    }
+}
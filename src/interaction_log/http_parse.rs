@@ -0,0 +1,144 @@
+//! A small parser for the raw requests of HTTP interaction logs.
+//!
+//! Only the request line and headers are parsed; the body is ignored.
+
+use super::ParsedLogEntry;
+
+/// The structured parts of an HTTP request logged by an Interactsh server
+///
+/// See [parse_http_request()](ParsedLogEntry::parse_http_request()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequestParts {
+    /// The request method (ex. "GET" or "POST")
+    pub method: String,
+    /// The request target (ex. "/index.html?a=b")
+    pub path: String,
+    /// The HTTP version (ex. "HTTP/1.1")
+    pub version: String,
+    /// The request headers, in the order they were sent
+    pub headers: Vec<(String, String)>,
+}
+
+impl ParsedLogEntry {
+    /// Parses the method, path, version, and headers from the raw request
+    /// of an HTTP log entry.
+    ///
+    /// Returns `None` if this is not an HTTP log entry or if the raw request
+    /// does not start with a well-formed request line. Malformed header
+    /// lines are skipped.
+    pub fn parse_http_request(&self) -> Option<HttpRequestParts> {
+        match self {
+            ParsedLogEntry::Http { raw_request, .. } => HttpRequestParts::parse(raw_request),
+            _ => None,
+        }
+    }
+}
+
+impl HttpRequestParts {
+    /// Parses the request line and headers of a raw HTTP request
+    fn parse(raw_request: &str) -> Option<Self> {
+        let mut lines = raw_request.lines();
+
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?;
+        let path = request_line.next()?;
+        let version = request_line.next()?;
+
+        let is_valid_request_line = request_line.next().is_none()
+            && !method.is_empty()
+            && method.bytes().all(|byte| byte.is_ascii_alphabetic())
+            && !path.is_empty()
+            && version.starts_with("HTTP/");
+        if !is_valid_request_line {
+            return None;
+        }
+
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+
+        Some(Self {
+            method: method.to_owned(),
+            path: path.to_owned(),
+            version: version.to_owned(),
+            headers,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    const UNIQUE_ID: &str = "cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb";
+
+    fn http_log_entry(raw_request: &str) -> ParsedLogEntry {
+        ParsedLogEntry::Http {
+            unique_id: UNIQUE_ID.into(),
+            full_id: UNIQUE_ID.into(),
+            raw_request: raw_request.into(),
+            raw_response: String::new(),
+            remote_address: "10.0.0.1".parse().unwrap(),
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[test]
+    fn http_request_is_parsed_from_raw_request() {
+        let log_entry = http_log_entry(
+            "POST /callback?id=1 HTTP/1.1\r\n\
+             Host: cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb.oast.pro\r\n\
+             User-Agent: curl/8.0.1\r\n\
+             X-Forwarded-For: 10.0.0.2, 10.0.0.3\r\n\
+             \r\n\
+             body: not a header",
+        );
+
+        let http_request = log_entry
+            .parse_http_request()
+            .expect("HTTP log did not return an HTTP request");
+
+        assert_eq!(http_request.method, "POST");
+        assert_eq!(http_request.path, "/callback?id=1");
+        assert_eq!(http_request.version, "HTTP/1.1");
+        assert_eq!(
+            http_request.headers,
+            vec![
+                (
+                    "Host".to_owned(),
+                    "cfk3ajm2vtc0000g3zg0gf7sijyyyyyyb.oast.pro".to_owned()
+                ),
+                ("User-Agent".to_owned(), "curl/8.0.1".to_owned()),
+                (
+                    "X-Forwarded-For".to_owned(),
+                    "10.0.0.2, 10.0.0.3".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_request_line_is_not_parsed() {
+        let malformed_requests = ["", "GET /", "GET / HTTP/1.1 extra", "GET / FTP/1.0"];
+
+        for raw_request in malformed_requests {
+            assert!(http_log_entry(raw_request).parse_http_request().is_none());
+        }
+    }
+
+    #[test]
+    fn http_request_is_none_for_other_protocols() {
+        let log_entry = ParsedLogEntry::Smb {
+            raw_request: "GET / HTTP/1.1\r\n\r\n".into(),
+            timestamp: OffsetDateTime::now_utc(),
+        };
+
+        assert!(log_entry.parse_http_request().is_none());
+    }
+}
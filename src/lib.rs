@@ -60,13 +60,88 @@
 //! - `native-tls-vendored`
 //!
 //! One of these must be enabled as well to use the crate as a client.
-//! `rustls-tls` is enabled by default.
+//! `rustls-tls` is enabled by default. If both Rustls and native TLS are
+//! enabled, Rustls is used unless `ClientBuilder::use_native_tls` is set on
+//! the builder.
 //!
 //! Note: All 3 TLS feature flags can also be used currently with the "reqwest-"
 //! prefix. These were the original TLS feature flag names used in initial
 //! development, but will be removed in a future release in favor of the shorter
 //! feature names omitting the "reqwest-" prefix.
 //!
+//! ## Feature Flags - Certificate pinning
+//! The `cert-pinning` feature flag enables
+//! [with_pinned_cert_sha256](crate::client::ClientBuilder::with_pinned_cert_sha256()),
+//! which makes the client only accept a server certificate with a specific
+//! SHA-256 hash. Pinning is implemented with Rustls, so this feature flag
+//! also enables `rustls-tls`.
+//!
+//! ## Feature Flags - Proxies
+//! HTTP and HTTPS proxies are supported by default. To use SOCKS proxies
+//! (see [with_proxy](crate::client::ClientBuilder::with_proxy())), enable the
+//! `socks` feature flag (or its alias, `socks-proxy`). Once enabled, a proxy such as
+//! `Proxy::all("socks5://host:1080")` is used for registering, polling, and
+//! deregistering.
+//!
+//! The `socks-proxy` name is a permanent alias for `socks`, for manifests that
+//! prefer to name the feature after what it enables. The `socks` feature flag
+//! can also be used currently with the name `reqwest-socks-proxy`, which will
+//! be removed in a future release.
+//!
+//! ## Feature Flags - Debugging
+//! The `test-utils` feature flag enables
+//! [last_decrypted_aes_key](crate::client::RegisteredClient::last_decrypted_aes_key()),
+//! which exposes the AES key the server used to encrypt the most recently polled logs.
+//! This is only meant for troubleshooting server interoperability and is never
+//! available in release builds.
+//!
+//! ## Feature Flags - Heuristics
+//! The `heuristics` feature flag enables
+//! [suspicion_score](crate::interaction_log::ParsedLogEntry::suspicion_score()),
+//! which gives a rough score of how likely a logged interaction was caused by a
+//! malicious payload. This is only meant as a triage aid.
+//!
+//! ## Feature Flags - HTTP request parsing
+//! The `http-parse` feature flag enables
+//! [parse_http_request](crate::interaction_log::ParsedLogEntry::parse_http_request()),
+//! which parses the method, path, version, and headers from the raw request of
+//! an HTTP log.
+//!
+//! ## Feature Flags - Parallel decryption
+//! The `parallel-decrypt` feature flag decrypts the logs returned by each poll
+//! in parallel using [rayon](https://github.com/rayon-rs/rayon). This can help
+//! clients that receive many logs per poll. The logs are still returned in the
+//! order the server sent them.
+//!
+//! ## Feature Flags - Tracing
+//! The `tracing` feature flag instruments
+//! [register](crate::client::UnregisteredClient::register()),
+//! [poll](crate::client::RegisteredClient::poll()), and
+//! [deregister](crate::client::RegisteredClient::deregister()) with
+//! [tracing](https://github.com/tokio-rs/tracing) spans, and emits an event
+//! with the number of logs returned by each poll. The spans record the
+//! server (as `peer.service`), correlation ID, poll status code (as
+//! `http.status_code`), and poll log count (as `interactsh.log_count`), but
+//! never the secret key or auth token. Nothing is instrumented when the
+//! feature flag is disabled.
+//!
+//! ## Feature Flags - OpenTelemetry
+//! The `opentelemetry` feature flag enables the `tracing` feature flag and
+//! adds the trace context of the current span to every request sent to the
+//! server, using the propagator registered with
+//! `opentelemetry::global::set_text_map_propagator`. To export the spans of
+//! the `tracing` feature flag as OpenTelemetry spans, add the layer from
+//! [tracing-opentelemetry](https://github.com/tokio-rs/tracing-opentelemetry)
+//! to your subscriber.
+//!
+//! ## Feature Flags - Blocking client
+//! The `blocking` feature flag enables the [blocking](crate::client::blocking)
+//! module, which has synchronous versions of the clients for tools that don't
+//! otherwise use async code. Build one with
+//! [build_blocking](crate::client::ClientBuilder::build_blocking()). These
+//! clients run their requests on a small internal tokio runtime, and must not
+//! be used from within an async context.
+//!
 //! ## Feature Flags - Async runtime compatibility
 //! This crate supports the [tokio](https://github.com/tokio-rs/tokio),
 //! [async-std](https://github.com/async-rs/async-std), and
@@ -77,7 +152,7 @@
 #![cfg_attr(feature = "nightly", feature(doc_auto_cfg))]
 
 #[cfg(any(feature = "rustcrypto", feature = "openssl"))]
-pub(crate) mod crypto;
+pub mod crypto;
 
 #[cfg(all(
     any(feature = "rustls-tls", feature = "native-tls"),
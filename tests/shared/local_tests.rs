@@ -50,25 +50,12 @@ pub async fn client_receives_http_logs_from_local_server() {
         None => panic!("No logs recieved from local server"),
     };
 
-    let mut log_entries = log_entries.into_iter();
-    while let Some(log_entry) = log_entries.next() {
-        match log_entry {
-            LogEntry::ParsedLog(parsed_log) => {
-                match parsed_log {
-                    ParsedLogEntry::Http { .. } => {
-                        client
-                            .deregister()
-                            .await
-                            .expect("Failed to deregister with the local server");
-
-                        return;
-                    }
-                    _ => continue,
-                }
-            }
-            LogEntry::RawLog(_) => continue,
-        }
+    if log_entries.http_only().next().is_none() {
+        panic!("No HTTP logs recieved from local server");
     }
 
-    panic!("No HTTP logs recieved from local server");
+    client
+        .deregister()
+        .await
+        .expect("Failed to deregister with the local server");
 }
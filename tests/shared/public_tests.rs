@@ -44,27 +44,14 @@ pub async fn client_receives_http_logs_from_pub_servers() {
         None => panic!("No logs recieved from public server"),
     };
 
-    let mut log_entries = log_entries.into_iter();
-    while let Some(log_entry) = log_entries.next() {
-        match log_entry {
-            LogEntry::ParsedLog(parsed_log) => {
-                match parsed_log {
-                    ParsedLogEntry::Http { .. } => {
-                        client
-                            .deregister()
-                            .await
-                            .expect("Failed to deregister with the public server");
-
-                        return;
-                    }
-                    _ => continue,
-                }
-            }
-            LogEntry::RawLog(_) => continue,
-        }
+    if log_entries.http_only().next().is_none() {
+        panic!("No HTTP logs recieved from public server");
     }
 
-    panic!("No HTTP logs recieved from public server");
+    client
+        .deregister()
+        .await
+        .expect("Failed to deregister with the public server");
 }
 
 
@@ -85,27 +72,14 @@ pub async fn client_receives_http_logs_from_proxied_pub_servers() {
         None => panic!("No logs recieved from public server"),
     };
 
-    let mut log_entries = log_entries.into_iter();
-    while let Some(log_entry) = log_entries.next() {
-        match log_entry {
-            LogEntry::ParsedLog(parsed_log) => {
-                match parsed_log {
-                    ParsedLogEntry::Http { .. } => {
-                        client
-                            .deregister()
-                            .await
-                            .expect("Failed to deregister with the public server");
-
-                        return;
-                    }
-                    _ => continue,
-                }
-            }
-            LogEntry::RawLog(_) => continue,
-        }
+    if log_entries.http_only().next().is_none() {
+        panic!("No HTTP logs recieved from public server");
     }
 
-    panic!("No HTTP logs recieved from public server");
+    client
+        .deregister()
+        .await
+        .expect("Failed to deregister with the public server");
 }
 
 
@@ -125,25 +99,12 @@ pub async fn client_receives_dns_logs_from_pub_servers() {
         None => panic!("No logs recieved from public server"),
     };
 
-    let mut log_entries = log_entries.into_iter();
-    while let Some(log_entry) = log_entries.next() {
-        match log_entry {
-            LogEntry::ParsedLog(parsed_log) => {
-                match parsed_log {
-                    ParsedLogEntry::Dns { .. } => {
-                        client
-                            .deregister()
-                            .await
-                            .expect("Failed to deregister with the public server");
-
-                        return;
-                    }
-                    _ => continue,
-                }
-            }
-            LogEntry::RawLog(_) => continue,
-        }
+    if log_entries.dns_only().next().is_none() {
+        panic!("No DNS logs recieved from public server");
     }
 
-    panic!("No DNS logs recieved from public server");
+    client
+        .deregister()
+        .await
+        .expect("Failed to deregister with the public server");
 }
@@ -35,6 +35,7 @@ pub fn build_pub_client(server: String, proxy: Option<Proxy>) -> UnregisteredCli
 pub async fn try_register_to_any_of_pub_servers(proxy: Option<Proxy>) -> RegisteredClient {
     let mut pub_servers = DEFAULT_INTERACTSH_SERVERS.iter();
 
+    #[allow(clippy::while_let_on_iterator)]
     while let Some(server) = pub_servers.next() {
         let unregistered_client = build_pub_client(server.to_string(), proxy.clone());
         let register_result = unregistered_client.register().await;